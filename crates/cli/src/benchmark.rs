@@ -2,18 +2,21 @@ use anyhow::{Context as _, Result};
 use bollard::Docker;
 use itertools::Itertools;
 use serde::Deserialize;
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use crate::{
-    config::{Config, Gateway},
-    docker::{self, ContainerId},
-    gateway::wait_for_gateway_health_with_logs,
+    config::{Config, Gateway, ScenarioConfig},
+    correctness::{self, Correctness},
+    docker,
+    gateway::{GATEWAY_PORT, GatewayInstance, wait_for_gateway_health_with_logs},
+    images,
     k6::{self, K6Run},
-    resources::{DockerStatsCollector, ResourceStats},
+    load::{self, LoadRequest},
+    resources::{ResourceStats, StatsCollector},
+    selector::{self, Selector},
 };
 
 pub fn create_benchmarks<S: AsRef<str>>(
-    docker: &Docker,
     config: &Config,
     gateways: &[S],
     scenarios: &[S],
@@ -27,20 +30,23 @@ pub fn create_benchmarks<S: AsRef<str>>(
         let supergraph_config = config.get_supergraph(&scenario_config.supergraph)?;
         let gateway = config.get_gateway(gateway_name)?;
 
-        benchmarks.push(Benchmark {
-            docker: docker.clone(),
-            scenario_name: scenario_name.to_string(),
-            scenario_path: config.current_dir.join("scenarios").join(scenario_name),
-            supergraph_path: config
-                .current_dir
-                .join("supergraphs")
-                .join(&scenario_config.supergraph),
-            subgraphs: supergraph_config.subgraphs.clone(),
-            compose_env: scenario_config.env.clone(),
-            gateway,
-            project_dir: config.current_dir.clone(),
-            container_id: None,
-        });
+        // Gateways declaring versions are benchmarked once per version.
+        for gateway in gateway.expand_versions() {
+            benchmarks.push(Benchmark {
+                scenario_name: scenario_name.to_string(),
+                scenario_path: config.current_dir.join("scenarios").join(scenario_name),
+                supergraph_path: config
+                    .current_dir
+                    .join("supergraphs")
+                    .join(&scenario_config.supergraph),
+                subgraphs: supergraph_config.subgraphs.clone(),
+                subgraphs_port: supergraph_config.port,
+                scenario: scenario_config.clone(),
+                gateway,
+                project_dir: config.current_dir.clone(),
+                gateway_instance: None,
+            });
+        }
     }
 
     benchmarks.sort_by(|a, b| {
@@ -52,8 +58,55 @@ pub fn create_benchmarks<S: AsRef<str>>(
     Ok(benchmarks)
 }
 
+/// Create the benchmarks for all the selected gateways and scenarios. Benchmarks matching any of
+/// the `exclude` selectors, either by gateway or scenario, are skipped.
+pub fn select_benchmarks(
+    config: &Config,
+    gateways: &[Selector],
+    scenarios: &[Selector],
+    exclude: &[Selector],
+) -> Result<Vec<Benchmark>> {
+    // Versions are selected individually as `<name>@<version>`, including those which aren't
+    // declared in `versions`.
+    let mut candidates: Vec<Arc<Gateway>> = config
+        .gateways
+        .iter()
+        .flat_map(|gateway| gateway.expand_versions())
+        .collect();
+    for selector in gateways {
+        if let Selector::Name(name) = selector
+            && name.contains('@')
+            && !name.contains(['*', '?', '[', '{'])
+            && !candidates.iter().any(|gateway| gateway.name() == name)
+        {
+            candidates.push(config.get_gateway(name)?);
+        }
+    }
+    let gateways = selector::select(
+        "gateway",
+        candidates.iter().map(|g| (g.name(), g.tags())),
+        gateways,
+        exclude,
+    )?;
+    let scenarios = selector::select(
+        "scenario",
+        config
+            .scenarios
+            .iter()
+            .map(|(name, scenario)| (name.as_str(), scenario.tags.as_slice())),
+        scenarios,
+        exclude,
+    )?;
+
+    create_benchmarks(config, &gateways, &scenarios)
+}
+
 /// Load benchmarks from a configuration file
-pub fn load_benchmarks(docker: &Docker, config: &Config, name: &str) -> Result<Vec<Benchmark>> {
+pub fn load_benchmarks(
+    config: &Config,
+    name: &str,
+    exclude: &[Selector],
+) -> Result<Vec<Benchmark>> {
     // Load benchmark configuration
     let config_path = config
         .current_dir
@@ -75,27 +128,21 @@ pub fn load_benchmarks(docker: &Docker, config: &Config, name: &str) -> Result<V
 
     let mut benchmarks = Vec::new();
 
-    // Process each benchmark entry
+    // Process each benchmark entry, empty gateways or scenarios select all of them.
     for entry in &benchmark_config.benchmarks {
-        // If gateways are empty, use all available gateways
-        let gateways = if entry.gateway.is_empty() {
-            config
-                .gateways
-                .iter()
-                .map(|g| g.name().to_string())
-                .collect()
-        } else {
-            entry.gateway.clone()
-        };
-
-        // If scenarios are empty, use all available scenarios
-        let scenarios = if entry.scenario.is_empty() {
-            config.scenarios.keys().cloned().collect()
-        } else {
-            entry.scenario.clone()
-        };
-
-        benchmarks.extend(create_benchmarks(docker, config, &gateways, &scenarios)?);
+        let exclude = entry
+            .exclude
+            .iter()
+            .map(|s| Selector::parse(s))
+            .chain(exclude.iter().cloned())
+            .collect::<Vec<_>>();
+
+        benchmarks.extend(select_benchmarks(
+            config,
+            &entry.gateway_selectors(),
+            &entry.scenario_selectors(),
+            &exclude,
+        )?);
     }
 
     benchmarks.sort_by(|a, b| {
@@ -103,36 +150,74 @@ pub fn load_benchmarks(docker: &Docker, config: &Config, name: &str) -> Result<V
             .cmp(&b.scenario_name)
             .then_with(|| a.gateway.name().cmp(b.gateway.name()))
     });
+    // Entries may overlap with globs and tags
+    benchmarks.dedup_by(|a, b| {
+        a.scenario_name == b.scenario_name && a.gateway.name() == b.gateway.name()
+    });
 
     Ok(benchmarks)
 }
 
 #[derive(Debug, Deserialize)]
-struct BenchmarkConfig {
-    benchmarks: Vec<BenchmarkEntry>,
+pub struct BenchmarkConfig {
+    pub benchmarks: Vec<BenchmarkEntry>,
 }
 
 #[serde_with::serde_as]
 #[derive(Debug, Deserialize)]
-struct BenchmarkEntry {
+pub struct BenchmarkEntry {
+    #[serde(default)]
+    #[serde_as(as = "serde_with::OneOrMany<_>")]
+    pub scenario: Vec<String>,
     #[serde(default)]
     #[serde_as(as = "serde_with::OneOrMany<_>")]
-    scenario: Vec<String>,
+    pub gateway: Vec<String>,
     #[serde(default)]
     #[serde_as(as = "serde_with::OneOrMany<_>")]
-    gateway: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl BenchmarkEntry {
+    pub fn gateway_selectors(&self) -> Vec<Selector> {
+        self.gateway.iter().map(|s| Selector::parse(s)).collect()
+    }
+
+    pub fn scenario_selectors(&self) -> Vec<Selector> {
+        self.scenario.iter().map(|s| Selector::parse(s)).collect()
+    }
+}
+
+/// Rough estimate of the time spent outside of the load test itself for each benchmark: cleaning
+/// up, starting the subgraphs and the gateway, waiting for it to be healthy and K6's graceful stop.
+const BENCHMARK_OVERHEAD: Duration = Duration::from_secs(15);
+
+/// Everything a benchmark would execute, resolved without touching docker.
+pub struct BenchmarkPlan {
+    /// Build of the gateway image tagged with its content hash, if built locally, followed by
+    /// the tag of the gateway image. The actual build is skipped if the image is up to date.
+    pub build_commands: Vec<String>,
+    pub compose_command: String,
+    pub gateway_command: String,
+    pub load_script: PathBuf,
+    pub duration: Duration,
+}
+
+impl BenchmarkPlan {
+    pub fn estimated_wall_time(&self) -> Duration {
+        self.duration + BENCHMARK_OVERHEAD
+    }
 }
 
 pub struct Benchmark {
-    docker: Docker,
     scenario_name: String,
     scenario_path: PathBuf,
     supergraph_path: PathBuf,
     subgraphs: Vec<String>,
-    compose_env: HashMap<String, String>,
+    subgraphs_port: u16,
+    scenario: ScenarioConfig,
     gateway: Arc<Gateway>,
     project_dir: PathBuf,
-    container_id: Option<ContainerId>,
+    gateway_instance: Option<GatewayInstance>,
 }
 
 #[derive(Debug)]
@@ -141,6 +226,9 @@ pub struct BenchmarkResult {
     pub gateway: Arc<Gateway>,
     pub k6_run: K6Run,
     pub resource_stats: ResourceStats,
+    /// Verdict of the response check done before the load, if the scenario has an expected
+    /// response.
+    pub correctness: Option<Correctness>,
 }
 
 impl BenchmarkResult {
@@ -237,6 +325,13 @@ impl BenchmarkResult {
     pub fn subgraph_request_count(&self) -> u64 {
         self.k6_run.summary.subgraph_stats.count
     }
+
+    /// Share of subgraph responses served from their cache, if enabled
+    pub fn subgraph_cache_hit_rate(&self) -> Option<f64> {
+        let stats = &self.k6_run.summary.subgraph_stats;
+        let total = stats.cache_hit + stats.cache_miss;
+        (total > 0).then(|| stats.cache_hit as f64 / total as f64)
+    }
 }
 
 impl Benchmark {
@@ -248,29 +343,124 @@ impl Benchmark {
         &self.gateway
     }
 
-    pub async fn run(&mut self, duration: Option<&str>) -> Result<BenchmarkResult> {
+    /// Port of the subgraphs service, serving their stats.
+    pub fn subgraphs_port(&self) -> u16 {
+        self.subgraphs_port
+    }
+
+    /// Requests of the scenario load.
+    pub fn requests(&self) -> Result<Vec<LoadRequest>> {
+        self.scenario.requests(&self.scenario_path)
+    }
+
+    /// Set an environment variable of the subgraphs, on top of the scenario ones.
+    pub fn set_subgraph_env(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.scenario.env.insert(key.into(), value.into());
+    }
+
+    /// K6 script of the scenario, generated from its load if it has one.
+    fn load_script(&self) -> Result<PathBuf> {
+        match &self.scenario.load {
+            Some(load) => load.write_script(&self.scenario_path, self.subgraphs_port),
+            None => Ok(self.scenario_path.join("k6.js")),
+        }
+    }
+
+    /// Resolve the commands this benchmark would run.
+    pub fn plan(&self, duration: Option<&str>) -> Result<BenchmarkPlan> {
+        let compose_command = docker::format_command(
+            self.scenario
+                .env
+                .iter()
+                .sorted()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+            "docker",
+            &docker::compose_up_args(&self.subgraphs),
+        );
+        let gateway_command = self.gateway.command_line(&self.supergraph_path);
+        let build_commands = match &self.gateway.config.build {
+            Some(build) => {
+                images::build_commands(&self.project_dir, &self.gateway.config.image, build)?
+                    .to_vec()
+            }
+            None => Vec::new(),
+        };
+        // Nothing is written for a dry run, the generated script is only described by its load.
+        let (load_script, duration) = match &self.scenario.load {
+            Some(load) => (
+                self.scenario_path.join(load::GENERATED_SCRIPT),
+                k6::parse_duration(duration.unwrap_or(&load.duration))?,
+            ),
+            None => {
+                let load_script = self.scenario_path.join("k6.js");
+                let duration = k6::test_duration(&load_script, duration)?;
+                (load_script, duration)
+            }
+        };
+
+        Ok(BenchmarkPlan {
+            build_commands,
+            compose_command,
+            gateway_command,
+            load_script,
+            duration,
+        })
+    }
+
+    /// Remove any leftover container from previous runs and ensure the gateway and subgraph
+    /// ports are free.
+    pub async fn prepare(&self, docker: &Docker) -> Result<()> {
+        docker::cleanup(docker, &[GATEWAY_PORT, self.subgraphs_port]).await
+    }
+
+    /// Start the subgraphs and the gateway, waiting for the latter to be healthy.
+    pub async fn start(&mut self) -> Result<&GatewayInstance> {
         // Start subgraphs using the main compose file with specific services
-        docker::compose_up(&self.project_dir, &self.subgraphs, &self.compose_env)?;
+        docker::compose_up(&self.project_dir, &self.subgraphs, &self.scenario.env)?;
 
         // Start gateway with supergraph mount
-        let container_id = self.gateway.start_with_supergraph(&self.supergraph_path)?;
-        self.container_id = Some(container_id.clone());
-
-        // Start metrics collection
-        let collector = DockerStatsCollector::start(self.docker.clone(), &container_id).await?;
+        let gateway = self
+            .gateway_instance
+            .insert(self.gateway.start_with_supergraph(&self.supergraph_path)?);
 
         // Start log streaming and wait for gateway to be healthy
-        wait_for_gateway_health_with_logs(&container_id).await?;
+        wait_for_gateway_health_with_logs(gateway).await?;
+
+        Ok(gateway)
+    }
+
+    pub async fn run(
+        &mut self,
+        docker: &Docker,
+        duration: Option<&str>,
+    ) -> Result<BenchmarkResult> {
+        let gateway = self.start().await?;
+
+        // Start metrics collection, only the samples taken during the load are kept.
+        let collector = StatsCollector::start(docker.clone(), gateway).await?;
+
+        // Check the responses once before the load, k6 checks only compare their size.
+        let correctness = correctness::check_gateway(&self.requests()?).await?;
+        if let Some(correctness) = &correctness
+            && !correctness.is_correct()
+        {
+            tracing::warn!(
+                "Incorrect response with {} difference(s):\n{}",
+                correctness.differences,
+                correctness.excerpt.join("\n")
+            );
+        }
 
         // Run K6 test from scenario directory
-        let k6_script_path = self.scenario_path.join("k6.js");
+        let k6_script_path = self.load_script()?;
         if !k6_script_path.exists() {
             return Err(anyhow::anyhow!(
                 "K6 script not found at {:?}",
                 k6_script_path
             ));
         }
-        let k6_run = k6::run(&self.scenario_path, "k6.js", duration).await?;
+        k6::reset_subgraph_stats(self.subgraphs_port).await?;
+        let k6_run = k6::run(&self.scenario_path, &k6_script_path, duration).await?;
 
         // Stop collection and get filtered stats
         let resource_stats = collector.stop_and_filter(k6_run.start, k6_run.end).await?;
@@ -281,15 +471,16 @@ impl Benchmark {
             gateway: self.gateway.clone(),
             k6_run,
             resource_stats,
+            correctness,
         })
     }
 
     pub async fn cleanup(self) {
-        // Stop gateway container if it exists
-        if let Some(container_id) = self.container_id
-            && let Err(e) = docker::stop(&container_id)
+        // Stop gateway if it was started
+        if let Some(gateway) = self.gateway_instance
+            && let Err(e) = gateway.stop()
         {
-            tracing::error!("Failed to stop container: {}", e);
+            tracing::error!("Failed to stop gateway: {}", e);
         }
 
         // Stop subgraphs
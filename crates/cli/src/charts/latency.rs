@@ -169,8 +169,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway A".to_string(),
                     image: "gateway-a:latest".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
             Arc::new(Gateway {
@@ -179,8 +184,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway B".to_string(),
                     image: "gateway-b:v2.0".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
         ];
@@ -195,7 +205,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 100 },
+                        subgraph_stats: SubgraphStats {
+                            count: 100,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -226,6 +240,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
             // Gateway B has failures and should be excluded
             BenchmarkResult {
@@ -238,7 +253,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 100 },
+                        subgraph_stats: SubgraphStats {
+                            count: 100,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -269,6 +288,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
         ];
 
@@ -292,8 +312,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway A".to_string(),
                     image: "gateway-a:latest".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
             Arc::new(Gateway {
@@ -302,8 +327,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway B".to_string(),
                     image: "gateway-b:v2.0".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
         ];
@@ -318,7 +348,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 100 },
+                        subgraph_stats: SubgraphStats {
+                            count: 100,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -349,6 +383,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
             BenchmarkResult {
                 scenario: "test-scenario".to_string(),
@@ -360,7 +395,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 100 },
+                        subgraph_stats: SubgraphStats {
+                            count: 100,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -391,6 +430,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
         ];
 
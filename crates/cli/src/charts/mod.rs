@@ -1,21 +1,26 @@
 mod efficiency;
 mod latency;
+mod operations;
 mod quality;
+mod versions;
 
 use efficiency::generate_efficiency_chart_to_file;
 use latency::generate_latency_chart_to_file;
+use operations::{generate_operations_chart_to_file, operation_names};
 use quality::generate_quality_chart_to_file;
+use versions::generate_versions_chart_to_file;
 
 use crate::benchmark::BenchmarkResult;
-use crate::config::Config;
+use crate::config::{Config, Gateway};
 use plotters::prelude::*;
 use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::Arc;
 
 /// Write all charts for the benchmark results to the specified directory
 pub fn write_charts(
     results: &[BenchmarkResult],
-    _config: &Config,
+    config: &Config,
     dir: &Path,
 ) -> anyhow::Result<()> {
     // Create the output directory if it doesn't exist
@@ -37,6 +42,17 @@ pub fn write_charts(
         let latency_path = dir.join(&latency_filename);
         generate_latency_chart_to_file(&benchmark_name, &benchmark_results, &latency_path)?;
 
+        // Generate the per-operation latency chart of workload mixes
+        if !operation_names(&benchmark_results).is_empty() {
+            let operations_filename =
+                format!("{}-operations.svg", benchmark_name.replace(' ', "-"));
+            generate_operations_chart_to_file(
+                &benchmark_name,
+                &benchmark_results,
+                &dir.join(operations_filename),
+            )?;
+        }
+
         // Generate efficiency chart
         let efficiency_filename = format!("{}-efficiency.svg", benchmark_name.replace(' ', "-"));
         let efficiency_path = dir.join(&efficiency_filename);
@@ -46,11 +62,80 @@ pub fn write_charts(
         let quality_filename = format!("{}-quality.svg", benchmark_name.replace(' ', "-"));
         let quality_path = dir.join(&quality_filename);
         generate_quality_chart_to_file(&benchmark_name, &benchmark_results, &quality_path)?;
+
+        // Generate a version-trend chart for each gateway benchmarked with multiple versions
+        for (gateway, versions) in group_by_version(&benchmark_results, config)? {
+            let versions_path = dir.join(versions_chart_filename(&benchmark_name, &gateway));
+            generate_versions_chart_to_file(
+                &benchmark_name,
+                gateway.label(),
+                &versions
+                    .iter()
+                    .map(|(version, result)| (version.as_str(), *result))
+                    .collect::<Vec<_>>(),
+                &versions_path,
+            )?;
+        }
     }
 
     Ok(())
 }
 
+/// Result of each version of a gateway, if any.
+pub type VersionResults<'a> = Vec<(String, Option<&'a BenchmarkResult>)>;
+
+/// Results of gateways benchmarked with multiple versions, grouped by gateway. Versions are in
+/// the order declared in config.toml, followed by any other version found in the results.
+pub fn group_by_version<'a>(
+    results: &[&'a BenchmarkResult],
+    config: &Config,
+) -> anyhow::Result<Vec<(Arc<Gateway>, VersionResults<'a>)>> {
+    let mut grouped: BTreeMap<&str, Vec<&BenchmarkResult>> = BTreeMap::new();
+    for result in results {
+        if result.gateway.config.version.is_some() {
+            grouped
+                .entry(result.gateway.base_name())
+                .or_default()
+                .push(result);
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|(name, results)| {
+            let gateway = config.get_gateway(name)?;
+            let mut versions: Vec<String> = gateway.config.versions.clone();
+            for result in &results {
+                let version = result.gateway.config.version.clone().unwrap_or_default();
+                if !versions.contains(&version) {
+                    versions.push(version);
+                }
+            }
+
+            let versions = versions
+                .into_iter()
+                .map(|version| {
+                    let result = results
+                        .iter()
+                        .find(|r| r.gateway.config.version.as_ref() == Some(&version))
+                        .copied();
+                    (version, result)
+                })
+                .collect();
+            Ok((gateway, versions))
+        })
+        .collect()
+}
+
+/// File name of the version-trend chart of a gateway for a scenario.
+pub fn versions_chart_filename(scenario_name: &str, gateway: &Gateway) -> String {
+    format!(
+        "{}-{}-versions.svg",
+        scenario_name.replace(' ', "-"),
+        gateway.base_name()
+    )
+}
+
 // Chart dimensions
 const CHART_WIDTH: u32 = 900; // Total width including legend
 const CHART_HEIGHT: u32 = 600;
@@ -212,4 +297,14 @@ pub mod tests {
     };
     pub use crate::resources::ResourceStats;
     pub use std::time::Duration;
+
+    /// Text elements of a chart, such as its title, labels and legend.
+    pub fn svg_texts(svg: &str) -> Vec<&str> {
+        svg.split("<text")
+            .skip(1)
+            .filter_map(|text| text.split_once('>')?.1.split_once("</text>"))
+            .map(|(text, _)| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
 }
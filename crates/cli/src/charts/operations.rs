@@ -0,0 +1,249 @@
+use super::*;
+use crate::benchmark::BenchmarkResult;
+use plotters::prelude::*;
+use std::collections::BTreeSet;
+
+/// Names of the operations of a workload mix found in the results.
+pub fn operation_names<'a>(results: &[&'a BenchmarkResult]) -> Vec<&'a str> {
+    results
+        .iter()
+        .flat_map(|r| r.k6_run.summary.operations.keys())
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// P95 latency of each operation of a workload mix, grouped by operation.
+pub fn generate_operations_chart(
+    scenario_name: &str,
+    results: &[&BenchmarkResult],
+) -> anyhow::Result<String> {
+    use plotters::style::IntoFont;
+
+    let operations = operation_names(results);
+
+    let mut buffer = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut buffer, (CHART_WIDTH, CHART_HEIGHT)).into_drawing_area();
+
+        root.fill(&CHART_BACKGROUND)?;
+
+        let legend_width = calculate_legend_width(results);
+        let (chart_area, legend_area) = root.split_horizontally(CHART_WIDTH - legend_width);
+
+        // Valid gateways sorted by overall median, as in the latency chart
+        let mut gateway_results: Vec<&BenchmarkResult> =
+            results.iter().filter(|r| r.is_valid()).copied().collect();
+        gateway_results.sort_by(|a, b| a.median_latency().total_cmp(&b.median_latency()));
+
+        let color_map = create_color_map(results);
+
+        let max_latency = gateway_results
+            .iter()
+            .flat_map(|r| r.k6_run.summary.operations.values())
+            .map(|metric| metric.values.p95)
+            .fold(0.0f64, |acc, val| acc.max(val));
+        let y_max = (max_latency * 1.1).ceil().max(1.0);
+
+        let num_operations = operations.len();
+        let num_gateways = gateway_results.len().max(1);
+
+        let mut chart = ChartBuilder::on(&chart_area)
+            .caption(
+                format!("{} - p95 latency per operation", scenario_name),
+                (FONT_FAMILY, TITLE_FONT_SIZE).into_font(),
+            )
+            .margin(CHART_MARGIN)
+            .x_label_area_size(X_LABEL_AREA_SIZE)
+            .y_label_area_size(Y_LABEL_AREA_SIZE)
+            .build_cartesian_2d(-0.5f64..(num_operations as f64 - 0.5), 0.0..y_max)?;
+
+        chart
+            .configure_mesh()
+            .y_desc("Latency (ms)")
+            .y_label_formatter(&|y| format!("{:.0}", y))
+            .x_label_formatter(&|x| {
+                let idx = (*x + 0.5) as usize;
+                operations.get(idx).unwrap_or(&"").to_string()
+            })
+            .x_labels(num_operations)
+            .x_label_style((FONT_FAMILY, LABEL_FONT_SIZE))
+            .y_label_style((FONT_FAMILY, LABEL_FONT_SIZE))
+            .disable_x_mesh()
+            .disable_y_mesh()
+            .draw()?;
+
+        let group_width = BAR_WIDTH_RATIO;
+        let bar_width = group_width / num_gateways as f64;
+
+        for (gateway_idx, result) in gateway_results.iter().enumerate() {
+            let color = color_map[result.gateway.label()];
+            let offset = -group_width / 2.0 + bar_width * (gateway_idx as f64 + 0.5);
+
+            let values: Vec<(f64, f64)> = operations
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, name)| {
+                    result
+                        .k6_run
+                        .summary
+                        .operations
+                        .get(*name)
+                        .map(|metric| (idx as f64, metric.values.p95))
+                })
+                .collect();
+
+            chart.draw_series(values.iter().map(|(x, y)| {
+                Rectangle::new(
+                    [
+                        (x + offset - bar_width / 2.0, 0.0),
+                        (x + offset + bar_width / 2.0, *y),
+                    ],
+                    ShapeStyle::from(color).filled(),
+                )
+            }))?;
+
+            for (x, y) in &values {
+                let decimal_places = if *y < 100.0 { 1 } else { 0 };
+                chart.draw_series(std::iter::once(Text::new(
+                    format!("{:.prec$}", y, prec = decimal_places),
+                    (x + offset, *y + (y_max * VALUE_LABEL_Y_OFFSET_RATIO)),
+                    (FONT_FAMILY, VALUE_FONT_SIZE)
+                        .into_font()
+                        .transform(FontTransform::Rotate270)
+                        .color(&BLACK),
+                )))?;
+            }
+        }
+
+        draw_legend_all(&legend_area, results, &color_map)?;
+
+        root.present()?;
+    }
+
+    Ok(buffer)
+}
+
+pub fn generate_operations_chart_to_file(
+    scenario_name: &str,
+    results: &[&BenchmarkResult],
+    output_path: &std::path::Path,
+) -> anyhow::Result<()> {
+    let svg_content = generate_operations_chart(scenario_name, results)?;
+    std::fs::write(output_path, svg_content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{charts::tests::*, config::Gateway};
+
+    fn trend(med: f64) -> TrendMetric {
+        TrendMetric {
+            values: TrendValues {
+                count: 100,
+                avg: med,
+                min: med / 2.0,
+                med,
+                max: med * 4.0,
+                p90: med * 1.5,
+                p95: med * 2.0,
+                p99: med * 3.0,
+            },
+        }
+    }
+
+    #[test]
+    fn should_chart_the_latency_of_each_operation() {
+        let gateway = Arc::new(Gateway {
+            name: "a".to_string(),
+            gateways_path: std::path::PathBuf::from("/test/gateways"),
+            config: crate::config::GatewayConfig {
+                label: "Gateway A".to_string(),
+                image: "gateway-a:latest".to_string(),
+                command: None,
+                args: vec![],
+                env: HashMap::new(),
+                tags: vec![],
+                versions: vec![],
+                version: None,
+                build: None,
+            },
+        });
+        let result = BenchmarkResult {
+            scenario: "mixed".to_string(),
+            gateway,
+            k6_run: K6Run {
+                start: time::OffsetDateTime::now_utc(),
+                end: time::OffsetDateTime::now_utc(),
+                summary: K6Summary {
+                    state: K6SummaryState {
+                        test_run_duration_ms: 60000.0,
+                    },
+                    subgraph_stats: SubgraphStats {
+                        count: 100,
+                        ..Default::default()
+                    },
+                    operations: BTreeMap::from([
+                        ("me".to_string(), trend(5.0)),
+                        ("top-products".to_string(), trend(20.0)),
+                    ]),
+                    metrics: K6SummaryMetrics {
+                        http_req_duration: Some(trend(6.0)),
+                        checks: Some(CheckMetric {
+                            values: HttpReqFailedValues { fails: 0 },
+                        }),
+                        http_reqs: None,
+                    },
+                },
+            },
+            resource_stats: ResourceStats {
+                cpu_usage_avg: 0.5,
+                cpu_usage_max: 0.8,
+                cpu_usage_std: 0.1,
+                memory_mib_avg: 256.0,
+                memory_mib_max: 512.0,
+                memory_mib_std: 50.0,
+                throttled_time: Duration::from_secs(0),
+                count: 100,
+            },
+            correctness: None,
+        };
+
+        let refs = vec![&result];
+        insta::assert_debug_snapshot!(operation_names(&refs), @r#"
+        [
+            "me",
+            "top-products",
+        ]
+        "#);
+
+        let svg = generate_operations_chart("mixed", &refs).unwrap();
+        let texts = svg_texts(&svg);
+        insta::assert_debug_snapshot!(texts, @r#"
+        [
+            "mixed - p95 latency per operation",
+            "Latency (ms)",
+            "0",
+            "5",
+            "10",
+            "15",
+            "20",
+            "25",
+            "30",
+            "35",
+            "40",
+            "me",
+            "top-products",
+            "10.0",
+            "40.0",
+            "Gateway A",
+        ]
+        "#);
+    }
+}
@@ -188,8 +188,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway A".to_string(),
                     image: "gateway-a:latest".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
             Arc::new(Gateway {
@@ -198,8 +203,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway B".to_string(),
                     image: "gateway-b:v2.0".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
         ];
@@ -215,7 +225,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 500 },
+                        subgraph_stats: SubgraphStats {
+                            count: 500,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -246,6 +260,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
             BenchmarkResult {
                 scenario: "test-scenario".to_string(),
@@ -257,7 +272,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 600 },
+                        subgraph_stats: SubgraphStats {
+                            count: 600,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -288,6 +307,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
         ];
 
@@ -0,0 +1,316 @@
+use super::*;
+use crate::benchmark::BenchmarkResult;
+use crate::k6::TrendValues;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+
+const POINT_SIZE: u32 = 4;
+const LINE_WIDTH: u32 = 2;
+
+type Percentile = fn(&TrendValues) -> f64;
+
+/// Chart of the latencies and CPU efficiency of a gateway across its versions, in the order they
+/// are declared in config.toml. Versions with failures have no data point.
+pub fn generate_versions_chart(
+    scenario_name: &str,
+    gateway_label: &str,
+    versions: &[(&str, Option<&BenchmarkResult>)],
+) -> anyhow::Result<String> {
+    use plotters::style::IntoFont;
+
+    let mut buffer = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut buffer, (CHART_WIDTH, CHART_HEIGHT)).into_drawing_area();
+
+        // Chart background
+        root.fill(&CHART_BACKGROUND)?;
+
+        // Split main area into title and chart areas
+        let (title_area, chart_area) = root.split_vertically(40);
+
+        // Add title centered in the title area
+        let title_text = format!("{} - {} versions", scenario_name, gateway_label);
+        let title_style = TextStyle::from((FONT_FAMILY, TITLE_FONT_SIZE).into_font())
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        title_area.draw(&Text::new(
+            title_text,
+            (title_area.dim_in_pixel().0 as i32 / 2, 20),
+            title_style,
+        ))?;
+
+        let labels: Vec<&str> = versions.iter().map(|(version, _)| *version).collect();
+        let valid: Vec<(usize, &BenchmarkResult)> = versions
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (_, result))| result.filter(|r| r.is_valid()).map(|r| (idx, r)))
+            .collect();
+
+        // Latencies on the left, CPU efficiency on the right
+        let panels: Vec<_> = chart_area.split_evenly((1, 2));
+
+        let percentiles: [(&str, Percentile); 3] = [
+            ("Median", |v| v.med),
+            ("p95", |v| v.p95),
+            ("p99", |v| v.p99),
+        ];
+        let latency_series: Vec<(&str, Vec<(usize, f64)>)> = percentiles
+            .into_iter()
+            .map(|(name, value_fn)| {
+                let points = valid
+                    .iter()
+                    .filter_map(|(idx, result)| {
+                        result
+                            .k6_run
+                            .summary
+                            .metrics
+                            .http_req_duration
+                            .as_ref()
+                            .map(|metric| (*idx, value_fn(&metric.values)))
+                    })
+                    .collect();
+                (name, points)
+            })
+            .collect();
+        draw_versions_panel(&panels[0], "Latency (ms)", &labels, &latency_series)?;
+
+        let efficiency_series = vec![(
+            "Requests/Core·s",
+            valid
+                .iter()
+                .map(|(idx, result)| (*idx, result.requests_per_core_s()))
+                .collect(),
+        )];
+        draw_versions_panel(&panels[1], "Requests/Core·s", &labels, &efficiency_series)?;
+
+        root.present()?;
+    }
+
+    Ok(buffer)
+}
+
+fn draw_versions_panel(
+    area: &DrawingArea<SVGBackend, plotters::coord::Shift>,
+    caption: &str,
+    labels: &[&str],
+    series: &[(&str, Vec<(usize, f64)>)],
+) -> anyhow::Result<()> {
+    use plotters::style::IntoFont;
+
+    let max_value = series
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|(_, v)| *v))
+        .fold(0.0f64, |acc, val| acc.max(val));
+    // Avoid an empty range if every version failed
+    let y_max = (max_value * 1.1).ceil().max(1.0);
+    let x_range = -0.5f64..(labels.len() as f64 - 0.5);
+
+    // Add caption at the top of the panel
+    let (caption_area, chart_area) = area.split_vertically(30);
+    caption_area.draw(&Text::new(
+        caption,
+        (caption_area.dim_in_pixel().0 as i32 / 2, 15),
+        TextStyle::from((FONT_FAMILY, CAPTION_FONT_SIZE).into_font())
+            .pos(Pos::new(HPos::Center, VPos::Center)),
+    ))?;
+
+    let mut chart = ChartBuilder::on(&chart_area)
+        .margin(PANEL_MARGIN)
+        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .y_label_area_size(Y_LABEL_AREA_SIZE_SMALL)
+        .build_cartesian_2d(x_range, 0.0..y_max)?;
+
+    chart
+        .configure_mesh()
+        .y_label_formatter(&|y| {
+            if *y >= KILO_THRESHOLD {
+                format!("{:.0}k", y / KILO_THRESHOLD)
+            } else {
+                format!("{:.0}", y)
+            }
+        })
+        .x_label_formatter(&|x| {
+            let idx = x.round();
+            if (x - idx).abs() > f64::EPSILON || idx < 0.0 {
+                return String::new();
+            }
+            labels.get(idx as usize).unwrap_or(&"").to_string()
+        })
+        .x_labels(labels.len() * 2 + 1)
+        .x_label_style((FONT_FAMILY, LABEL_FONT_SIZE))
+        .y_label_style((FONT_FAMILY, LABEL_FONT_SIZE))
+        .disable_x_mesh()
+        .draw()?;
+
+    for (idx, (name, points)) in series.iter().enumerate() {
+        let color = GATEWAY_COLORS[idx % GATEWAY_COLORS.len()];
+        let points: Vec<(f64, f64)> = points.iter().map(|(x, y)| (*x as f64, *y)).collect();
+
+        chart
+            .draw_series(LineSeries::new(
+                points.clone(),
+                color.stroke_width(LINE_WIDTH),
+            ))?
+            .label(*name)
+            .legend(move |(x, y)| {
+                Rectangle::new(
+                    [
+                        (x, y - LEGEND_BOX_SIZE / 2),
+                        (x + LEGEND_BOX_SIZE, y + LEGEND_BOX_SIZE / 2),
+                    ],
+                    color.filled(),
+                )
+            });
+        chart.draw_series(
+            points
+                .into_iter()
+                .map(|point| Circle::new(point, POINT_SIZE, color.filled())),
+        )?;
+    }
+
+    if series.len() > 1 {
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(CHART_BACKGROUND)
+            .label_font((FONT_FAMILY, LEGEND_FONT_SIZE))
+            .draw()?;
+    }
+
+    Ok(())
+}
+
+pub fn generate_versions_chart_to_file(
+    scenario_name: &str,
+    gateway_label: &str,
+    versions: &[(&str, Option<&BenchmarkResult>)],
+    output_path: &std::path::Path,
+) -> anyhow::Result<()> {
+    let svg_content = generate_versions_chart(scenario_name, gateway_label, versions)?;
+    std::fs::write(output_path, svg_content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{charts::tests::*, config::Gateway};
+
+    #[test]
+    fn should_chart_each_version_including_missing_ones() {
+        let gateway = Gateway {
+            name: "a".to_string(),
+            gateways_path: std::path::PathBuf::from("/test/gateways"),
+            config: crate::config::GatewayConfig {
+                label: "Gateway A".to_string(),
+                image: "gateway-a:latest".to_string(),
+                command: None,
+                args: vec![],
+                env: HashMap::new(),
+                tags: vec![],
+                versions: vec!["1.0".to_string(), "1.1".to_string(), "main".to_string()],
+                version: None,
+                build: None,
+            },
+        };
+
+        let results = ["1.0", "main"]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, version)| BenchmarkResult {
+                scenario: "test-scenario".to_string(),
+                gateway: Arc::new(gateway.with_version(version)),
+                k6_run: K6Run {
+                    start: time::OffsetDateTime::now_utc(),
+                    end: time::OffsetDateTime::now_utc(),
+                    summary: K6Summary {
+                        state: K6SummaryState {
+                            test_run_duration_ms: 60000.0,
+                        },
+                        subgraph_stats: SubgraphStats {
+                            count: 500,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
+                        metrics: K6SummaryMetrics {
+                            http_req_duration: Some(TrendMetric {
+                                values: TrendValues {
+                                    count: 100,
+                                    avg: 25.0,
+                                    min: 10.0,
+                                    med: 20.0 - idx as f64 * 5.0,
+                                    max: 100.0,
+                                    p90: 35.0,
+                                    p95: 45.0,
+                                    p99: 80.0,
+                                },
+                            }),
+                            checks: Some(CheckMetric {
+                                values: HttpReqFailedValues { fails: 0 },
+                            }),
+                            http_reqs: None,
+                        },
+                    },
+                },
+                resource_stats: ResourceStats {
+                    cpu_usage_avg: 0.5,
+                    cpu_usage_max: 0.8,
+                    cpu_usage_std: 0.1,
+                    memory_mib_avg: 256.0,
+                    memory_mib_max: 512.0,
+                    memory_mib_std: 50.0,
+                    throttled_time: Duration::from_secs(0),
+                    count: 100,
+                },
+                correctness: None,
+            })
+            .collect::<Vec<_>>();
+
+        let versions = [
+            ("1.0", Some(&results[0])),
+            ("1.1", None),
+            ("main", Some(&results[1])),
+        ];
+        let svg = generate_versions_chart("Test Scenario", "Gateway A", &versions).unwrap();
+
+        let texts = svg_texts(&svg);
+        insta::assert_debug_snapshot!(texts, @r#"
+        [
+            "Test Scenario - Gateway A versions",
+            "Latency (ms)",
+            "0",
+            "10",
+            "20",
+            "30",
+            "40",
+            "50",
+            "60",
+            "70",
+            "80",
+            "1.0",
+            "1.1",
+            "main",
+            "Median",
+            "p95",
+            "p99",
+            "Requests/Core·s",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0",
+            "0",
+            "1",
+            "1",
+            "1",
+            "1",
+            "1",
+            "1.0",
+            "1.1",
+            "main",
+        ]
+        "#);
+    }
+}
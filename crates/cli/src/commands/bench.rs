@@ -1,42 +1,70 @@
+use std::path::PathBuf;
+
 use argh::FromArgs;
 
-use crate::commands::Context;
+use crate::{
+    commands::Context,
+    results::{RunDir, RunSettings},
+    selector::Selector,
+};
 
 #[derive(FromArgs)]
 #[argh(subcommand, name = "bench")]
 /// Run benchmarks with specific gateways and scenarios
 pub struct Command {
-    /// comma-separated gateway names (e.g., "grafbase,apollo-router")
+    /// comma-separated gateway names, globs or tags (e.g., "grafbase,*-no-cache,tag:rust")
     #[argh(option, default = "String::new()", short = 'g')]
     pub gateway: String,
 
-    /// comma-separated scenario names (e.g., "big-response,many-plans")
+    /// comma-separated scenario names, globs or tags (e.g., "big-response,many-*")
     #[argh(option, default = "String::new()", short = 's')]
     pub scenario: String,
 
+    /// comma-separated gateway or scenario names, globs or tags to exclude
+    #[argh(option, default = "String::new()", short = 'x')]
+    pub exclude: String,
+
     /// override K6 test duration (e.g., "30s", "1m", "2m30s")
     #[argh(option, short = 'd')]
     pub duration: Option<String>,
+
+    /// resume an interrupted run from its results directory (e.g. "results/2025-09-23T10-00-00Z")
+    #[argh(option)]
+    pub resume: Option<PathBuf>,
+
+    /// print what would be run and the estimated wall time without running anything
+    #[argh(switch)]
+    pub dry_run: bool,
 }
 
 pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
-    // Parse comma-separated gateways and scenarios
-    let gateways: Vec<&str> = if cmd.gateway.is_empty() {
-        ctx.config.gateways.iter().map(|g| g.name()).collect()
-    } else {
-        cmd.gateway.split(',').map(|s| s.trim()).collect()
-    };
-    let scenarios: Vec<&str> = if cmd.scenario.is_empty() {
-        ctx.config
-            .scenarios
-            .keys()
-            .map(|key| key.as_str())
-            .collect()
-    } else {
-        cmd.scenario.split(',').map(|s| s.trim()).collect()
-    };
-    let benchmarks =
-        crate::benchmark::create_benchmarks(&ctx.docker, &ctx.config, &gateways, &scenarios)?;
-
-    super::run::run_benchmarks(benchmarks, &ctx.config, cmd.duration.as_deref()).await
+    // Parse comma-separated gateways and scenarios, empty ones select all of them
+    let benchmarks = crate::benchmark::select_benchmarks(
+        &ctx.config,
+        &Selector::parse_list(&cmd.gateway),
+        &Selector::parse_list(&cmd.scenario),
+        &Selector::parse_list(&cmd.exclude),
+    )?;
+
+    let settings = RunSettings::new(&ctx.config, cmd.duration.as_deref())?;
+    if cmd.dry_run {
+        let run_dir = cmd
+            .resume
+            .as_deref()
+            .map(|path| RunDir::open(path, &settings))
+            .transpose()?;
+        return super::run::print_plan(&benchmarks, cmd.duration.as_deref(), run_dir.as_ref());
+    }
+
+    let run_dir =
+        RunDir::create_or_resume(&ctx.config.current_dir, cmd.resume.as_deref(), &settings)?;
+
+    super::run::run_benchmarks(
+        &ctx.docker()?,
+        benchmarks,
+        &ctx.config,
+        cmd.duration.as_deref(),
+        run_dir,
+    )
+    .await
 }
@@ -0,0 +1,95 @@
+use argh::FromArgs;
+
+use crate::{
+    commands::Context,
+    config::{Config, SupergraphConfig},
+    docker, supergraph,
+};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "check-schemas")]
+/// Check the committed supergraphs match the schemas served by the subgraphs
+pub struct Command {
+    /// supergraphs to check, all of them by default
+    #[argh(positional)]
+    pub supergraphs: Vec<String>,
+
+    /// also fail on definitions served by the subgraphs but not exposed by the supergraph
+    #[argh(switch)]
+    pub strict: bool,
+
+    /// use the already running subgraphs instead of starting them with docker compose
+    #[argh(switch)]
+    pub no_start: bool,
+}
+
+pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
+    let config = &ctx.config;
+    let supergraphs = supergraph::select(config, &cmd.supergraphs)?;
+
+    if !cmd.no_start {
+        supergraph::start_subgraphs(&ctx.docker()?, config, &supergraphs).await?;
+    }
+
+    let result = check_all(config, &supergraphs, cmd.strict).await;
+
+    if !cmd.no_start
+        && let Err(e) = docker::compose_down(&config.current_dir)
+    {
+        tracing::error!("Failed to stop subgraphs: {}", e);
+    }
+
+    let mismatches = result?;
+    if mismatches > 0 {
+        return Err(anyhow::anyhow!(
+            "Found {} mismatch(es) between the supergraphs and the subgraphs",
+            mismatches
+        ));
+    }
+
+    println!("All supergraphs match their subgraphs");
+    Ok(())
+}
+
+/// Diff every subgraph of the given supergraphs, returning the number of mismatches failing the
+/// check.
+async fn check_all(
+    config: &Config,
+    supergraphs: &[(&String, &SupergraphConfig)],
+    strict: bool,
+) -> anyhow::Result<usize> {
+    let client = reqwest::Client::new();
+    let mut count = 0;
+
+    for (name, _) in supergraphs {
+        let supergraph_dir = config.current_dir.join("supergraphs").join(name);
+        let schema_path = supergraph_dir.join("schema.graphql");
+        let schema = std::fs::read_to_string(&schema_path)?;
+
+        for graph in supergraph::read_join_graphs(&supergraph_dir)? {
+            let expected = supergraph::supergraph_definitions(&schema, &graph)?;
+            let sdl = supergraph::fetch_sdl(&client, &graph).await?;
+            let served = supergraph::subgraph_definitions(&sdl)?;
+
+            let diff = supergraph::diff_definitions(&expected, &served);
+            if diff.missing.is_empty() && diff.extra.is_empty() {
+                println!("{}/{}: ok", name, graph.name);
+                continue;
+            }
+
+            println!("{}/{}:", name, graph.name);
+            for missing in &diff.missing {
+                println!("  - {missing} is missing from the subgraph");
+            }
+            for extra in &diff.extra {
+                println!("  - {extra} is not exposed by the supergraph");
+            }
+            count += diff.missing.len();
+            if strict {
+                count += diff.extra.len();
+            }
+        }
+    }
+
+    Ok(count)
+}
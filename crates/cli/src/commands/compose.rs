@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use argh::FromArgs;
+use duct::cmd;
+
+use crate::{
+    commands::Context,
+    composition::SubgraphSdl,
+    config::{Config, SupergraphConfig},
+    docker, supergraph,
+};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "compose")]
+/// Compose the supergraphs from the running subgraphs and check the committed files are up to date
+pub struct Command {
+    /// supergraphs to compose, all of them by default
+    #[argh(positional)]
+    pub supergraphs: Vec<String>,
+
+    /// overwrite the committed supergraph files instead of failing on drift
+    #[argh(switch)]
+    pub write: bool,
+
+    /// use the already running subgraphs instead of starting them with docker compose
+    #[argh(switch)]
+    pub no_start: bool,
+}
+
+pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
+    let config = &ctx.config;
+
+    let supergraphs = supergraph::select(config, &cmd.supergraphs)?;
+
+    if !cmd.no_start {
+        supergraph::start_subgraphs(&ctx.docker()?, config, &supergraphs).await?;
+    }
+
+    let result = compose_all(config, &supergraphs, cmd.write).await;
+
+    if !cmd.no_start
+        && let Err(e) = docker::compose_down(&config.current_dir)
+    {
+        tracing::error!("Failed to stop subgraphs: {}", e);
+    }
+
+    let outdated = result?;
+    if outdated > 0 {
+        return Err(anyhow::anyhow!(
+            "{} supergraph file(s) out of date, run `compose --write` to update them",
+            outdated
+        ));
+    }
+
+    Ok(())
+}
+
+/// Compose every supergraph, returning the number of committed files that are out of date.
+async fn compose_all(
+    config: &Config,
+    supergraphs: &[(&String, &SupergraphConfig)],
+    write: bool,
+) -> anyhow::Result<usize> {
+    let mut outdated = 0;
+
+    for (name, supergraph) in supergraphs {
+        if supergraph.compose.is_empty() {
+            println!(
+                "Supergraph '{}' has no composition configured, skipping",
+                name
+            );
+            continue;
+        }
+
+        let supergraph_dir = config.current_dir.join("supergraphs").join(name);
+        let work_dir = std::env::temp_dir()
+            .join("federation-benchmarks-compose")
+            .join(name);
+        std::fs::create_dir_all(&work_dir)?;
+
+        let subgraphs = fetch_subgraphs(supergraph, &work_dir).await?;
+
+        for compose in &supergraph.compose {
+            let composer = compose.composer();
+            let output = composer.compose(&subgraphs, &work_dir)?;
+            let committed_path = supergraph_dir.join(&compose.output);
+            let relative_path = PathBuf::from("supergraphs")
+                .join(name)
+                .join(&compose.output);
+
+            let up_to_date = match std::fs::read_to_string(&committed_path) {
+                Ok(committed) => composer.normalize(&committed)? == composer.normalize(&output)?,
+                Err(_) => false,
+            };
+
+            if up_to_date {
+                println!("{}: up to date", relative_path.display());
+            } else if write {
+                std::fs::write(&committed_path, &output)
+                    .with_context(|| format!("Could not write {:?}", committed_path))?;
+                println!("{}: updated", relative_path.display());
+            } else {
+                println!("{}: out of date", relative_path.display());
+                print_diff(&committed_path, &work_dir, &output)?;
+                outdated += 1;
+            }
+        }
+    }
+
+    Ok(outdated)
+}
+
+/// Fetch the SDL of every subgraph served by the compose service of the supergraph into
+/// `work_dir`. The committed supergraph isn't used, as it's the output of the composition.
+async fn fetch_subgraphs(
+    supergraph: &SupergraphConfig,
+    work_dir: &Path,
+) -> anyhow::Result<Vec<SubgraphSdl>> {
+    let client = reqwest::Client::new();
+    let mut subgraphs = Vec::new();
+
+    for (name, sdl) in supergraph::fetch_service_sdls(&client, supergraph.port).await? {
+        let path = work_dir.join(format!("{name}.graphql"));
+        std::fs::write(&path, sdl)?;
+        subgraphs.push(SubgraphSdl {
+            url: format!("http://localhost:{}/graphql/{name}", supergraph.port),
+            name,
+            path,
+        });
+    }
+
+    Ok(subgraphs)
+}
+
+fn print_diff(committed_path: &Path, work_dir: &Path, output: &str) -> anyhow::Result<()> {
+    let composed_path = work_dir.join(
+        committed_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid supergraph path {:?}", committed_path))?,
+    );
+    std::fs::write(&composed_path, output)?;
+
+    // diff exits with 1 when the files differ
+    let diff = cmd!("diff", "-u", committed_path, &composed_path)
+        .unchecked()
+        .read()?;
+    println!("{diff}");
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use anyhow::Context as _;
+use argh::FromArgs;
+use bollard::Docker;
+
+use crate::{
+    benchmark::create_benchmarks,
+    commands::Context,
+    config::Config,
+    correctness::{self, Expected},
+    docker,
+    gateway::GATEWAY_PORT,
+    images,
+    load::{LoadRequest, Validation},
+    supergraph,
+};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "fixtures")]
+/// Generate the expected responses of the scenarios from a reference gateway
+pub struct Command {
+    /// scenarios to generate the fixtures of, all of them by default
+    #[argh(positional)]
+    pub scenarios: Vec<String>,
+
+    /// reference gateway, used for supergraphs with multiple subgraphs
+    #[argh(option, short = 'g', default = "String::from(\"grafbase\")")]
+    pub gateway: String,
+
+    /// only report the fixtures which are out of date, without writing them
+    #[argh(switch)]
+    pub check: bool,
+}
+
+pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
+    let config = &ctx.config;
+    let docker = ctx.docker()?;
+
+    let scenarios: Vec<String> = if cmd.scenarios.is_empty() {
+        config.scenarios.keys().cloned().collect()
+    } else {
+        cmd.scenarios.clone()
+    };
+
+    let gateway = config.get_gateway(&cmd.gateway)?;
+    images::build_gateway_images(config, std::iter::once(gateway.as_ref()))?;
+
+    let mut outdated = 0;
+    for scenario_name in &scenarios {
+        let responses = reference_responses(config, &docker, scenario_name, &cmd.gateway)
+            .await
+            .with_context(|| {
+                format!("Failed to get the responses of scenario '{scenario_name}'")
+            })?;
+
+        for (request, response) in responses {
+            let label = request.label(scenario_name);
+            if !update_fixture(&label, &request.fixture_dir, response, cmd.check)? {
+                outdated += 1;
+            }
+        }
+    }
+
+    if outdated > 0 {
+        return Err(anyhow::anyhow!(
+            "{} fixture(s) out of date, run `fixtures` without --check to update them",
+            outdated
+        ));
+    }
+
+    Ok(())
+}
+
+/// Responses of the scenario requests, directly from the subgraph for supergraphs with a single
+/// one, from the reference gateway otherwise.
+async fn reference_responses(
+    config: &Config,
+    docker: &Docker,
+    scenario_name: &str,
+    gateway: &str,
+) -> anyhow::Result<Vec<(LoadRequest, Response)>> {
+    let scenario = config.get_scenario(scenario_name)?;
+    let scenario_path = config.current_dir.join("scenarios").join(scenario_name);
+    let supergraph_dir = config
+        .current_dir
+        .join("supergraphs")
+        .join(&scenario.supergraph);
+    // Responses of subgraphs injecting faults vary, so they have no fixture.
+    if scenario
+        .load
+        .as_ref()
+        .is_some_and(|load| matches!(load.validation, Some(Validation::Errors)))
+    {
+        return Ok(Vec::new());
+    }
+    let graphs = supergraph::read_join_graphs(&supergraph_dir)?;
+    // Responses to generated variables vary, so they have no fixture.
+    let requests: Vec<LoadRequest> = scenario
+        .requests(&scenario_path)?
+        .into_iter()
+        .filter(LoadRequest::is_static)
+        .collect();
+    if requests.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let responses = if let [graph] = graphs.as_slice() {
+        let supergraph = config.get_supergraph(&scenario.supergraph)?;
+        docker::cleanup(docker, &[supergraph.port]).await?;
+        docker::compose_up(&config.current_dir, &supergraph.subgraphs, &scenario.env)?;
+
+        let responses = send_requests(&graph.url, &requests).await;
+
+        if let Err(e) = docker::compose_down(&config.current_dir) {
+            tracing::error!("Failed to stop subgraphs: {}", e);
+        }
+        responses?
+    } else {
+        let mut benchmark = create_benchmarks(config, &[gateway], &[scenario_name])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No reference gateway '{}'", gateway))?;
+        benchmark.prepare(docker).await?;
+
+        let responses = match benchmark.start().await {
+            Ok(_) => {
+                send_requests(
+                    &format!("http://localhost:{GATEWAY_PORT}/graphql"),
+                    &requests,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+
+        benchmark.cleanup().await;
+        responses?
+    };
+
+    Ok(requests.into_iter().zip(responses).collect())
+}
+
+/// Reference response with the size of its body as received.
+struct Response {
+    value: serde_json::Value,
+    size: usize,
+}
+
+/// Send each request once, rejecting any unsuccessful response.
+async fn send_requests(url: &str, requests: &[LoadRequest]) -> anyhow::Result<Vec<Response>> {
+    let mut responses = Vec::with_capacity(requests.len());
+    for request in requests {
+        let (status, body) = correctness::send_request(url, request.body.clone()).await?;
+        let label = request.operation.as_deref().unwrap_or("request");
+        if status != 200 {
+            return Err(anyhow::anyhow!("HTTP status {} for {}", status, label));
+        }
+        let response: serde_json::Value = serde_json::from_slice(&body)?;
+        if response.get("errors").is_some() {
+            return Err(anyhow::anyhow!(
+                "Response of {} has errors: {}",
+                label,
+                response["errors"]
+            ));
+        }
+        responses.push(Response {
+            value: response,
+            size: body.len(),
+        });
+    }
+    Ok(responses)
+}
+
+/// Compare the response with the current fixture and update it, returning `false` if it's out of
+/// date and was left as is.
+fn update_fixture(
+    label: &str,
+    fixture_dir: &Path,
+    response: Response,
+    check: bool,
+) -> anyhow::Result<bool> {
+    let canonical = correctness::canonical_json(&response.value);
+    let fixture = Expected::from_response(response.value, response.size);
+
+    match Expected::load(fixture_dir)? {
+        Some(current) => {
+            let correctness = current.check(200, canonical.as_bytes());
+            if !correctness.is_correct() {
+                println!(
+                    "{label}: {} difference(s) with the current fixture",
+                    correctness.differences
+                );
+                for difference in &correctness.excerpt {
+                    println!("  {difference}");
+                }
+            } else if current.response_size() != fixture.response_size() {
+                println!(
+                    "{label}: response size {} instead of {:?} in the current fixture",
+                    response.size,
+                    current.response_size()
+                );
+            } else {
+                println!("{label}: up to date");
+                return Ok(true);
+            }
+        }
+        None => println!("{label}: no fixture"),
+    }
+
+    if check {
+        return Ok(false);
+    }
+
+    std::fs::create_dir_all(fixture_dir)?;
+    fixture.write(fixture_dir)?;
+    println!("{label}: updated");
+    Ok(true)
+}
@@ -13,7 +13,7 @@ pub struct Command {
 
 pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
     // List details for specific config
-    let benchmarks = load_benchmarks(&ctx.docker, &ctx.config, &cmd.name)?;
+    let benchmarks = load_benchmarks(&ctx.config, &cmd.name, &[])?;
     if benchmarks.is_empty() {
         println!("No benchmarks found in config '{}'", cmd.name);
         return Ok(());
@@ -1,17 +1,27 @@
 pub mod bench;
+pub mod check_schemas;
+pub mod compose;
+pub mod fixtures;
 pub mod list;
+pub mod plans;
 pub mod run;
+pub mod validate;
 
 use std::path::PathBuf;
 
 use argh::FromArgs;
 use bollard::Docker;
 
-use crate::config::Config;
+use crate::config::{Config, Override};
 
 #[derive(FromArgs)]
 /// GraphQL Federation Benchmark Runner
 pub struct CliArgs {
+    /// override a config.toml value, e.g. `scenarios.query.env.DELAY_MS=20`. Can be repeated and
+    /// takes precedence over `GFB_*` environment variables.
+    #[argh(option, long = "set")]
+    pub set: Vec<String>,
+
     #[argh(subcommand)]
     pub command: Command,
 }
@@ -20,20 +30,32 @@ pub struct CliArgs {
 #[argh(subcommand)]
 pub enum Command {
     Bench(bench::Command),
+    CheckSchemas(check_schemas::Command),
+    Compose(compose::Command),
+    Fixtures(fixtures::Command),
     List(list::Command),
+    Plans(plans::Command),
     Run(run::Command),
+    Validate(validate::Command),
 }
 
 pub struct Context {
-    docker: Docker,
     config: Config,
 }
 
 impl Context {
-    pub fn new(current_dir: PathBuf) -> anyhow::Result<Self> {
-        let docker = Docker::connect_with_local_defaults()
-            .map_err(|e| anyhow::anyhow!("Failed to connect to Docker: {}", e))?;
-        let config = Config::load(current_dir)?;
-        Ok(Self { docker, config })
+    pub fn new(current_dir: PathBuf, set: &[String]) -> anyhow::Result<Self> {
+        let mut overrides = Override::from_env(std::env::vars());
+        for value in set {
+            overrides.push(Override::parse(value)?);
+        }
+        let config = Config::load(current_dir, overrides)?;
+        Ok(Self { config })
+    }
+
+    /// Connect to Docker, only done by commands that actually need it.
+    pub fn docker(&self) -> anyhow::Result<Docker> {
+        Docker::connect_with_local_defaults()
+            .map_err(|e| anyhow::anyhow!("Failed to connect to Docker: {}", e))
     }
 }
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context as _;
+use argh::FromArgs;
+use serde::Deserialize;
+
+use crate::{
+    benchmark::{Benchmark, select_benchmarks},
+    commands::Context,
+    gateway::GATEWAY_PORT,
+    images,
+    load::LoadRequest,
+    selector::Selector,
+};
+
+/// Recording file of the subgraph requests, inside the subgraph containers.
+const RECORDING_PATH: &str = "/tmp/requests.jsonl";
+
+/// Maximum length of the queries printed.
+const QUERY_EXCERPT_LENGTH: usize = 120;
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "plans")]
+/// Show the subgraph requests each gateway sends for the requests of a scenario
+pub struct Command {
+    /// scenario to send the requests of
+    #[argh(positional)]
+    pub scenario: String,
+
+    /// comma-separated gateway names, globs or tags (e.g., "grafbase,tag:rust"), all by default
+    #[argh(option, default = "String::new()", short = 'g')]
+    pub gateway: String,
+
+    /// comma-separated gateway names, globs or tags to exclude
+    #[argh(option, default = "String::new()", short = 'x')]
+    pub exclude: String,
+}
+
+/// A subgraph request recorded by the subgraph service.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubgraphRequest {
+    pub subgraph: String,
+    pub query: String,
+    pub variables_size: usize,
+    /// Milliseconds since the UNIX epoch at which the request was received.
+    pub timestamp: f64,
+    /// Milliseconds spent answering the request.
+    pub latency: f64,
+}
+
+impl SubgraphRequest {
+    fn end(&self) -> f64 {
+        self.timestamp + self.latency
+    }
+}
+
+/// Subgraph requests sent by a gateway for a single request, grouped into sequential steps of
+/// concurrent requests.
+#[derive(Debug)]
+pub struct ObservedPlan {
+    pub steps: Vec<Vec<SubgraphRequest>>,
+}
+
+impl ObservedPlan {
+    /// A request starts a new step once all requests of the current one are answered.
+    pub fn new(mut requests: Vec<SubgraphRequest>) -> Self {
+        requests.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+
+        let mut steps: Vec<Vec<SubgraphRequest>> = Vec::new();
+        let mut step_end = f64::NEG_INFINITY;
+        for request in requests {
+            match steps.last_mut() {
+                Some(step) if request.timestamp < step_end => {
+                    step_end = step_end.max(request.end());
+                    step.push(request);
+                }
+                _ => {
+                    step_end = request.end();
+                    steps.push(vec![request]);
+                }
+            }
+        }
+        ObservedPlan { steps }
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.steps.iter().map(Vec::len).sum()
+    }
+}
+
+pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
+    let config = &ctx.config;
+    let benchmarks = select_benchmarks(
+        config,
+        &Selector::parse_list(&cmd.gateway),
+        &Selector::parse_list(&cmd.scenario),
+        &Selector::parse_list(&cmd.exclude),
+    )?;
+    images::build_gateway_images(config, benchmarks.iter().map(|b| b.gateway().as_ref()))?;
+    let docker = ctx.docker()?;
+
+    // Plans of each request of the scenario, by gateway
+    let mut plans: BTreeMap<String, Vec<(String, ObservedPlan)>> = BTreeMap::new();
+    for mut benchmark in benchmarks {
+        let gateway = benchmark.gateway().label().to_string();
+        benchmark.set_subgraph_env("RECORD_REQUESTS", RECORDING_PATH);
+        benchmark.prepare(&docker).await?;
+
+        let observed = match benchmark.start().await {
+            Ok(_) => observe(&benchmark).await,
+            Err(e) => Err(e),
+        };
+        let scenario = benchmark.name().to_string();
+        benchmark.cleanup().await;
+
+        match observed {
+            Ok(observed) => {
+                for (label, plan) in observed {
+                    plans
+                        .entry(format!("{scenario}/{label}"))
+                        .or_default()
+                        .push((gateway.clone(), plan));
+                }
+            }
+            Err(e) => tracing::error!("Could not observe the plans of {}: {:#}", gateway, e),
+        }
+    }
+
+    for (label, gateways) in plans {
+        println!("=== {label} ===");
+        for (gateway, plan) in gateways {
+            println!(
+                "\n{gateway}: {} subgraph request(s) in {} step(s)",
+                plan.request_count(),
+                plan.steps.len()
+            );
+            for (idx, step) in plan.steps.iter().enumerate() {
+                for (request_idx, request) in step.iter().enumerate() {
+                    let prefix = if request_idx == 0 {
+                        format!("{:>3}.", idx + 1)
+                    } else {
+                        "    ".to_string()
+                    };
+                    println!(
+                        "{prefix} {:<12} {:>7.1}ms {:>7}B vars  {}",
+                        request.subgraph,
+                        request.latency,
+                        request.variables_size,
+                        excerpt(&request.query)
+                    );
+                }
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Send each static request of the scenario once, and group the subgraph requests recorded
+/// while it was answered.
+async fn observe(benchmark: &Benchmark) -> anyhow::Result<Vec<(String, ObservedPlan)>> {
+    let client = reqwest::Client::new();
+    let requests: Vec<LoadRequest> = benchmark
+        .requests()?
+        .into_iter()
+        .filter(LoadRequest::is_static)
+        .collect();
+
+    let mut windows = Vec::with_capacity(requests.len());
+    for request in &requests {
+        let start = now();
+        client
+            .post(format!("http://localhost:{GATEWAY_PORT}/graphql"))
+            .header("Content-Type", "application/json")
+            .header("Authorization", "Bearer observed-plans")
+            .body(request.body.clone())
+            .send()
+            .await?
+            .bytes()
+            .await?;
+        windows.push((start, now()));
+    }
+
+    let recording = client
+        .get(format!(
+            "http://localhost:{}/requests",
+            benchmark.subgraphs_port()
+        ))
+        .send()
+        .await?
+        .error_for_status()
+        .context("Could not retrieve the recorded subgraph requests")?
+        .text()
+        .await?;
+    let recorded = recording
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<SubgraphRequest>)
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid recorded subgraph request")?;
+
+    Ok(requests
+        .iter()
+        .zip(windows)
+        .map(|(request, (start, end))| {
+            let label = request
+                .operation
+                .as_deref()
+                .unwrap_or("request")
+                .to_string();
+            let requests = recorded
+                .iter()
+                .filter(|r| start <= r.timestamp && r.timestamp <= end)
+                .cloned()
+                .collect();
+            (label, ObservedPlan::new(requests))
+        })
+        .collect())
+}
+
+/// Milliseconds since the UNIX epoch, the subgraph timestamps sharing the host clock.
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+        * 1000.0
+}
+
+fn excerpt(query: &str) -> String {
+    if query.chars().count() <= QUERY_EXCERPT_LENGTH {
+        return query.to_string();
+    }
+    let excerpt: String = query.chars().take(QUERY_EXCERPT_LENGTH).collect();
+    format!("{excerpt}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(subgraph: &str, timestamp: f64, latency: f64) -> SubgraphRequest {
+        SubgraphRequest {
+            subgraph: subgraph.to_string(),
+            query: "{ me { id } }".to_string(),
+            variables_size: 0,
+            timestamp,
+            latency,
+        }
+    }
+
+    #[test]
+    fn requests_should_start_a_new_step_once_the_previous_ones_are_answered() {
+        let plan = ObservedPlan::new(vec![
+            request("reviews", 12.0, 5.0),
+            request("accounts", 0.0, 10.0),
+            request("inventory", 11.0, 3.0),
+            request("products", 18.0, 1.0),
+        ]);
+
+        let steps: Vec<Vec<&str>> = plan
+            .steps
+            .iter()
+            .map(|step| step.iter().map(|r| r.subgraph.as_str()).collect())
+            .collect();
+        insta::assert_debug_snapshot!(steps, @r#"
+        [
+            [
+                "accounts",
+            ],
+            [
+                "inventory",
+                "reviews",
+            ],
+            [
+                "products",
+            ],
+        ]
+        "#);
+        assert_eq!(plan.request_count(), 4);
+    }
+}
@@ -1,10 +1,17 @@
+use std::{path::PathBuf, time::Duration};
+
 use argh::FromArgs;
+use bollard::Docker;
 
 use crate::{
     benchmark::{Benchmark, load_benchmarks},
     commands::Context,
     config::Config,
+    images,
+    k6::format_duration,
     report::{self, ReportOptions},
+    results::{RunDir, RunSettings},
+    selector::Selector,
     system::SystemInfo,
 };
 
@@ -19,50 +26,96 @@ pub struct Command {
     /// override K6 test duration (e.g., "30s", "1m", "2m30s")
     #[argh(option, short = 'd')]
     pub duration: Option<String>,
+
+    /// comma-separated gateway or scenario names, globs or tags to exclude
+    #[argh(option, default = "String::new()", short = 'x')]
+    pub exclude: String,
+
+    /// resume an interrupted run from its results directory (e.g. "results/2025-09-23T10-00-00Z")
+    #[argh(option)]
+    pub resume: Option<PathBuf>,
+
+    /// print what would be run and the estimated wall time without running anything
+    #[argh(switch)]
+    pub dry_run: bool,
 }
 
 pub async fn main(ctx: Context, cmd: Command) -> anyhow::Result<()> {
-    let benchmarks = load_benchmarks(&ctx.docker, &ctx.config, &cmd.name)?;
+    let benchmarks = load_benchmarks(&ctx.config, &cmd.name, &Selector::parse_list(&cmd.exclude))?;
 
-    run_benchmarks(benchmarks, &ctx.config, cmd.duration.as_deref()).await
+    let settings = RunSettings::new(&ctx.config, cmd.duration.as_deref())?;
+    if cmd.dry_run {
+        let run_dir = cmd
+            .resume
+            .as_deref()
+            .map(|path| RunDir::open(path, &settings))
+            .transpose()?;
+        return print_plan(&benchmarks, cmd.duration.as_deref(), run_dir.as_ref());
+    }
+
+    let run_dir =
+        RunDir::create_or_resume(&ctx.config.current_dir, cmd.resume.as_deref(), &settings)?;
+
+    run_benchmarks(
+        &ctx.docker()?,
+        benchmarks,
+        &ctx.config,
+        cmd.duration.as_deref(),
+        run_dir,
+    )
+    .await
 }
 
 pub async fn run_benchmarks(
+    docker: &Docker,
     benchmarks: Vec<Benchmark>,
     config: &Config,
     duration: Option<&str>,
+    run_dir: RunDir,
 ) -> anyhow::Result<()> {
-    // Clean up any existing Docker containers before starting
-    tracing::info!("Cleaning up existing Docker containers...");
-    let mut results = Vec::new();
+    tracing::info!("Storing results in {:?}", run_dir.path());
+
+    // Build all the local gateway images upfront rather than failing in the middle of a run.
+    images::build_gateway_images(
+        config,
+        benchmarks
+            .iter()
+            .filter(|benchmark| !run_dir.is_completed(benchmark))
+            .map(|b| b.gateway().as_ref()),
+    )?;
 
+    let mut results = Vec::new();
     for mut benchmark in benchmarks {
+        // Results of a previous attempt at this run are reported in the order of the benchmarks.
+        if let Some(result) = run_dir.load(&benchmark)? {
+            tracing::info!(
+                "Skipping benchmark '{}' with gateway '{}', already completed",
+                benchmark.name(),
+                benchmark.gateway().name()
+            );
+            results.push(result);
+            continue;
+        }
+
         tracing::info!(
             "=== Running benchmark '{}' with gateway '{}' ===",
             benchmark.name(),
             benchmark.gateway().name()
         );
-        let clean_result = std::process::Command::new("sh")
-            .arg("./docker-clean.sh")
-            .output();
-
-        match clean_result {
-            Ok(output) => {
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    // Don't fail if cleanup fails - there might be no containers to clean
-                    tracing::debug!("Docker cleanup script returned non-zero status: {}", stderr);
-                } else {
-                    tracing::info!("Docker cleanup completed successfully");
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Could not run docker cleanup script: {}", e);
-            }
+
+        // Clean up any existing Docker containers before starting
+        tracing::info!("Cleaning up existing Docker containers...");
+        if let Err(e) = benchmark.prepare(docker).await {
+            tracing::error!("Failed to clean up Docker containers: {}", e);
+            continue;
         }
+        tracing::info!("Docker cleanup completed successfully");
 
-        match benchmark.run(duration).await {
+        match benchmark.run(docker, duration).await {
             Ok(result) => {
+                if let Err(e) = run_dir.save(&result) {
+                    tracing::error!("Failed to store benchmark result: {}", e);
+                }
                 results.push(result);
             }
             Err(e) => {
@@ -109,3 +162,59 @@ pub async fn run_benchmarks(
 
     Ok(())
 }
+
+/// Print everything the benchmarks would execute without touching docker.
+pub fn print_plan(
+    benchmarks: &[Benchmark],
+    duration: Option<&str>,
+    run_dir: Option<&RunDir>,
+) -> anyhow::Result<()> {
+    println!("=== Plan ===");
+    let mut total = Duration::ZERO;
+    let mut skipped = 0;
+
+    for (i, benchmark) in benchmarks.iter().enumerate() {
+        println!(
+            "\n[{}/{}] Scenario '{}' with gateway '{}'",
+            i + 1,
+            benchmarks.len(),
+            benchmark.name(),
+            benchmark.gateway().name()
+        );
+
+        if run_dir
+            .as_ref()
+            .is_some_and(|run_dir| run_dir.is_completed(benchmark))
+        {
+            println!("  Already completed, skipped");
+            skipped += 1;
+            continue;
+        }
+
+        let plan = benchmark.plan(duration)?;
+        for build_command in &plan.build_commands {
+            println!("  Build:     {}", build_command);
+        }
+        println!("  Subgraphs: {}", plan.compose_command);
+        println!("  Gateway:   {}", plan.gateway_command);
+        println!(
+            "  Load:      k6 run {} ({})",
+            plan.load_script.display(),
+            format_duration(plan.duration)
+        );
+        total += plan.estimated_wall_time();
+    }
+
+    println!(
+        "\n{} benchmark(s) to run{}, estimated total wall time: {}",
+        benchmarks.len() - skipped,
+        if skipped > 0 {
+            format!(" ({skipped} already completed)")
+        } else {
+            String::new()
+        },
+        format_duration(total)
+    );
+
+    Ok(())
+}
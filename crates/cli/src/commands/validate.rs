@@ -0,0 +1,495 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use argh::FromArgs;
+use toml::de::{DeTable, DeValue};
+
+use crate::{benchmark::BenchmarkConfig, commands::Context, config::Config, load};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "validate")]
+/// Check config.toml, the benchmark configurations and all the files they reference
+pub struct Command {}
+
+pub async fn main(ctx: Context, _cmd: Command) -> anyhow::Result<()> {
+    let problems = validate(&ctx.config)?;
+
+    if problems.is_empty() {
+        println!("Configuration is valid");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{problem}");
+    }
+
+    Err(anyhow::anyhow!(
+        "Found {} problem(s) in the configuration",
+        problems.len()
+    ))
+}
+
+/// A single configuration problem, with the location it originates from.
+struct Problem {
+    file: PathBuf,
+    line: Option<usize>,
+    message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file.display(), line, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// A TOML file kept around to locate keys and values in it.
+struct TomlFile {
+    path: PathBuf,
+    content: String,
+}
+
+impl TomlFile {
+    fn read(current_dir: &Path, relative_path: PathBuf) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(current_dir.join(&relative_path))?;
+        Ok(Self {
+            path: relative_path,
+            content,
+        })
+    }
+
+    fn problem(&self, line: Option<usize>, message: String) -> Problem {
+        Problem {
+            file: self.path.clone(),
+            line,
+            message,
+        }
+    }
+
+    /// Line of the deepest key found along the given path. Numeric segments index arrays.
+    fn line(&self, keys: &[&str]) -> Option<usize> {
+        self.line_of_item(keys, None)
+    }
+
+    /// Line of a string item within the array found at the given path, falling back to the line
+    /// of the path itself.
+    fn line_of_item(&self, keys: &[&str], item: Option<&str>) -> Option<usize> {
+        let root = DeTable::parse(&self.content).ok()?;
+        let mut offset = None;
+        let mut table = Some(root.get_ref());
+        let mut value: Option<&DeValue<'_>> = None;
+
+        for key in keys {
+            if let Some(current) = table {
+                let Some((k, v)) = current.iter().find(|(k, _)| k.get_ref() == key) else {
+                    break;
+                };
+                offset = Some(k.span().start);
+                value = Some(v.get_ref());
+            } else if let Some(DeValue::Array(array)) = value
+                && let Ok(index) = key.parse::<usize>()
+                && let Some(element) = array.get(index)
+            {
+                offset = Some(element.span().start);
+                value = Some(element.get_ref());
+            } else {
+                break;
+            }
+
+            table = match value {
+                Some(DeValue::Table(table)) => Some(table),
+                _ => None,
+            };
+        }
+
+        if let Some(item) = item
+            && let Some(DeValue::Array(array)) = value
+            && let Some(element) = array
+                .iter()
+                .find(|element| matches!(element.get_ref(), DeValue::String(s) if s == item))
+        {
+            offset = Some(element.span().start);
+        }
+
+        offset.map(|offset| self.content[..offset].matches('\n').count() + 1)
+    }
+}
+
+fn validate(config: &Config) -> anyhow::Result<Vec<Problem>> {
+    let current_dir = &config.current_dir;
+    let config_file = TomlFile::read(current_dir, PathBuf::from("config.toml"))?;
+    let compose_services = compose_services(current_dir)?;
+    let mut problems = Vec::new();
+
+    // Scenarios
+    for (name, scenario) in &config.scenarios {
+        if !config.supergraphs.contains_key(&scenario.supergraph) {
+            problems.push(config_file.problem(
+                config_file.line(&["scenarios", name, "supergraph"]),
+                format!(
+                    "scenario '{}' references unknown supergraph '{}'",
+                    name, scenario.supergraph
+                ),
+            ));
+        }
+
+        let scenario_dir = current_dir.join("scenarios").join(name);
+        match &scenario.load {
+            Some(load) => {
+                // Files of the load with their keys within it.
+                let mut files = Vec::new();
+                for (key, file) in [("body", &load.body), ("query", &load.query)] {
+                    files.extend(file.iter().map(|file| (vec![key.to_string()], file)));
+                }
+                if let Some(replay) = &load.replay {
+                    files.push((vec!["replay".to_string(), "file".to_string()], &replay.file));
+                }
+                for (idx, operation) in load.operations.iter().enumerate() {
+                    for (key, file) in [("body", &operation.body), ("query", &operation.query)] {
+                        let keys = vec!["operations".to_string(), idx.to_string(), key.to_string()];
+                        files.extend(file.iter().map(|file| (keys.clone(), file)));
+                    }
+                }
+
+                let missing: Vec<_> = files
+                    .into_iter()
+                    .filter(|(_, file)| !load::scenario_file(&scenario_dir, file).exists())
+                    .collect();
+                for (keys, file) in &missing {
+                    let mut path = vec!["scenarios", name.as_str(), "load"];
+                    path.extend(keys.iter().map(String::as_str));
+                    problems.push(config_file.problem(
+                        config_file.line(&path),
+                        format!(
+                            "scenario '{}' load {} {:?} does not exist",
+                            name,
+                            keys.join("."),
+                            load::scenario_file(&scenario_dir, file)
+                        ),
+                    ));
+                }
+                if missing.is_empty()
+                    && let Err(e) = load.requests(&scenario_dir)
+                {
+                    problems.push(config_file.problem(
+                        config_file.line(&["scenarios", name, "load"]),
+                        format!("scenario '{}': {:#}", name, e),
+                    ));
+                }
+            }
+            None => {
+                if !scenario_dir.join("k6.js").exists() {
+                    problems.push(config_file.problem(
+                        config_file.line(&["scenarios", name]),
+                        format!(
+                            "scenario '{}' has neither a load nor a k6.js in {:?}",
+                            name, scenario_dir
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    for entry in std::fs::read_dir(current_dir.join("scenarios"))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_dir() && !config.scenarios.contains_key(&name) {
+            problems.push(Problem {
+                file: PathBuf::from("scenarios").join(&name),
+                line: None,
+                message: format!(
+                    "scenario directory '{}' is not declared in config.toml",
+                    name
+                ),
+            });
+        }
+    }
+
+    // Supergraphs
+    for (name, supergraph) in &config.supergraphs {
+        let schema_path = PathBuf::from("supergraphs")
+            .join(name)
+            .join("schema.graphql");
+        match std::fs::read_to_string(current_dir.join(&schema_path)) {
+            Ok(schema) if !schema.contains("@join__graph") => problems.push(Problem {
+                file: schema_path,
+                line: None,
+                message: format!(
+                    "supergraph '{}' schema does not define any join__graph",
+                    name
+                ),
+            }),
+            Ok(_) => {}
+            Err(_) => problems.push(config_file.problem(
+                config_file.line(&["supergraphs", name]),
+                format!("supergraph '{}' has no schema at {:?}", name, schema_path),
+            )),
+        }
+
+        for (idx, compose) in supergraph.compose.iter().enumerate() {
+            let output_path = PathBuf::from("supergraphs")
+                .join(name)
+                .join(&compose.output);
+            if !current_dir.join(&output_path).exists() {
+                problems.push(config_file.problem(
+                    config_file.line(&["supergraphs", name, "compose", &idx.to_string()]),
+                    format!(
+                        "supergraph '{}' composes '{}' which doesn't exist, run `compose --write` to create it",
+                        name,
+                        output_path.display()
+                    ),
+                ));
+            }
+        }
+
+        for subgraph in &supergraph.subgraphs {
+            if !compose_services.contains(subgraph) {
+                problems.push(config_file.problem(
+                    config_file.line_of_item(&["supergraphs", name, "subgraphs"], Some(subgraph)),
+                    format!(
+                        "supergraph '{}' references '{}' which isn't a service in compose.yml",
+                        name, subgraph
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Gateways
+    for gateway in &config.gateways {
+        let name = gateway.name();
+        let gateways_path = &gateway.gateways_path;
+
+        let values = gateway
+            .config
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| (vec!["args".to_string(), i.to_string()], arg))
+            .chain(
+                gateway
+                    .config
+                    .env
+                    .iter()
+                    .map(|(key, value)| (vec!["env".to_string(), key.clone()], value)),
+            );
+
+        for (keys, value) in values {
+            let mut location = vec!["gateways", name];
+            location.extend(keys.iter().map(String::as_str));
+            let line = config_file.line(&location);
+
+            let mut references = mounted_paths(value, "/supergraph/")
+                .map(str::to_string)
+                .collect::<BTreeSet<_>>();
+
+            // Process gateways reference host paths rather than mount points.
+            let gateways_mount_point = match gateway.config.command {
+                Some(_) => format!("{}/", gateways_path.display()),
+                None => "/gateways/".to_string(),
+            };
+            for path in mounted_paths(value, &gateways_mount_point) {
+                let host_path = gateways_path.join(path);
+                match std::fs::read_to_string(&host_path) {
+                    // Gateway configuration files may also reference supergraph files.
+                    Ok(content) => references
+                        .extend(mounted_paths(&content, "/supergraph/").map(str::to_string)),
+                    Err(_) => problems.push(config_file.problem(
+                        line,
+                        format!(
+                            "gateway '{}' references '{}' which doesn't exist at {:?}",
+                            name, value, host_path
+                        ),
+                    )),
+                }
+            }
+
+            // Supergraph files are mounted for every scenario, so they must exist for every
+            // supergraph.
+            for reference in references {
+                for supergraph in config.supergraphs.keys() {
+                    let supergraph_file = PathBuf::from("supergraphs")
+                        .join(supergraph)
+                        .join(&reference);
+                    if !current_dir.join(&supergraph_file).exists() {
+                        problems.push(config_file.problem(
+                            line,
+                            format!(
+                                "gateway '{}' requires '{}' which doesn't exist for supergraph '{}'",
+                                name,
+                                supergraph_file.display(),
+                                supergraph
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Benchmark configurations
+    let mut benchmark_files = std::fs::read_dir(current_dir.join("benchmarks"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    benchmark_files.sort();
+
+    for path in benchmark_files {
+        if path.extension().is_none_or(|ext| ext != "toml") {
+            continue;
+        }
+        let relative_path = path
+            .strip_prefix(current_dir)
+            .unwrap_or(&path)
+            .to_path_buf();
+        let file = TomlFile::read(current_dir, relative_path)?;
+
+        let benchmark_config: BenchmarkConfig = match toml::from_str(&file.content) {
+            Ok(benchmark_config) => benchmark_config,
+            Err(e) => {
+                let line = e
+                    .span()
+                    .map(|span| file.content[..span.start].matches('\n').count() + 1);
+                problems.push(file.problem(line, e.message().to_string()));
+                continue;
+            }
+        };
+
+        for (i, entry) in benchmark_config.benchmarks.iter().enumerate() {
+            let index = i.to_string();
+            for (raw, selector) in entry.gateway.iter().zip(entry.gateway_selectors()) {
+                if !config
+                    .gateways
+                    .iter()
+                    .flat_map(|g| g.expand_versions())
+                    .any(|g| selector.matches(g.name(), g.tags()))
+                {
+                    problems.push(file.problem(
+                        file.line_of_item(&["benchmarks", &index, "gateway"], Some(raw)),
+                        format!("no gateway matches '{}'", selector),
+                    ));
+                }
+            }
+            for (raw, selector) in entry.scenario.iter().zip(entry.scenario_selectors()) {
+                if !config
+                    .scenarios
+                    .iter()
+                    .any(|(name, scenario)| selector.matches(name, &scenario.tags))
+                {
+                    problems.push(file.problem(
+                        file.line_of_item(&["benchmarks", &index, "scenario"], Some(raw)),
+                        format!("no scenario matches '{}'", selector),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Paths relative to a mount point referenced in a string, e.g. `grafbase/grafbase.toml` for
+/// `/gateways/grafbase/grafbase.toml` with the `/gateways/` mount point.
+fn mounted_paths<'a>(value: &'a str, mount_point: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    value
+        .match_indices(mount_point)
+        .filter_map(move |(start, _)| {
+            let path = value[start + mount_point.len()..]
+                .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | ';'))
+                .next()
+                .unwrap_or_default();
+            (!path.is_empty()).then_some(path)
+        })
+}
+
+/// Names of the services declared in the root compose.yml.
+fn compose_services(current_dir: &Path) -> anyhow::Result<BTreeSet<String>> {
+    let content = std::fs::read_to_string(current_dir.join("compose.yml"))?;
+    let mut services = BTreeSet::new();
+    let mut in_services = false;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_services = line.trim_end() == "services:";
+        } else if in_services
+            && let Some(name) = line.strip_prefix("  ")
+            && !name.starts_with(' ')
+            && let Some(name) = name.trim_end().strip_suffix(':')
+        {
+            services.insert(name.to_string());
+        }
+    }
+
+    Ok(services)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_locate_the_line_of_a_key_or_its_closest_parent() {
+        let file = TomlFile {
+            path: PathBuf::from("test.toml"),
+            content: r#"
+[scenarios.query]
+supergraph = "fed"
+
+[[benchmarks]]
+scenario = "query"
+gateway = [
+    "grafbase",
+    "cosmo",
+]
+"#
+            .to_string(),
+        };
+
+        let lines = [
+            file.line(&["scenarios", "query"]),
+            file.line(&["scenarios", "query", "supergraph"]),
+            file.line(&["scenarios", "query", "unknown"]),
+            file.line(&["benchmarks", "0", "scenario"]),
+            file.line_of_item(&["benchmarks", "0", "gateway"], Some("cosmo")),
+        ];
+        insta::assert_debug_snapshot!(lines, @r#"
+        [
+            Some(
+                2,
+            ),
+            Some(
+                3,
+            ),
+            Some(
+                2,
+            ),
+            Some(
+                6,
+            ),
+            Some(
+                9,
+            ),
+        ]
+        "#);
+    }
+
+    #[test]
+    fn should_list_the_paths_mounted_in_a_gateway_config() {
+        let paths: Vec<_> = mounted_paths(
+            "router_config_path: /supergraph/cosmo/supergraph.json\npath: /supergraph/schema.graphql",
+            "/supergraph/",
+        )
+        .collect();
+        insta::assert_debug_snapshot!(paths, @r#"
+        [
+            "cosmo/supergraph.json",
+            "schema.graphql",
+        ]
+        "#);
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{Context as _, Result};
+use duct::cmd;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// A composition of a supergraph file, as configured in config.toml:
+///
+/// ```toml
+/// [supergraphs.fed]
+/// compose = [{ backend = "wgc", output = "cosmo/supergraph.json" }]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComposeConfig {
+    pub backend: ComposeBackend,
+    /// Output file, relative to the supergraph directory.
+    pub output: PathBuf,
+    /// Command running the backend, defaults to the backend tool from the PATH.
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComposeBackend {
+    /// Apollo `rover supergraph compose`, producing a supergraph SDL.
+    Rover,
+    /// Cosmo `wgc router compose`, producing the router execution config.
+    Wgc,
+}
+
+impl ComposeConfig {
+    pub fn composer(&self) -> Box<dyn Composer> {
+        match self.backend {
+            ComposeBackend::Rover => Box::new(Rover {
+                command: self.command.clone().unwrap_or_else(|| "rover".to_string()),
+            }),
+            ComposeBackend::Wgc => Box::new(Wgc {
+                command: self.command.clone().unwrap_or_else(|| "wgc".to_string()),
+            }),
+        }
+    }
+}
+
+/// A subgraph schema fetched from a running subgraph service.
+pub struct SubgraphSdl {
+    pub name: String,
+    pub url: String,
+    /// Path to the SDL file
+    pub path: PathBuf,
+}
+
+pub trait Composer {
+    /// Compose the supergraph from the subgraph schemas, using `dir` for any intermediate file.
+    fn compose(&self, subgraphs: &[SubgraphSdl], dir: &Path) -> Result<String>;
+
+    /// Content to compare with the committed file, without anything that changes on every
+    /// composition.
+    fn normalize(&self, output: &str) -> Result<String> {
+        Ok(output.trim_end().to_string())
+    }
+}
+
+struct Rover {
+    command: String,
+}
+
+impl Composer for Rover {
+    fn compose(&self, subgraphs: &[SubgraphSdl], dir: &Path) -> Result<String> {
+        let mut config = String::from("federation_version: 2\nsubgraphs:\n");
+        for subgraph in subgraphs {
+            config.push_str(&format!(
+                "  {}:\n    routing_url: {}\n    schema:\n      file: {}\n",
+                subgraph.name,
+                subgraph.url,
+                subgraph.path.display()
+            ));
+        }
+        let config_path = dir.join("supergraph.yaml");
+        std::fs::write(&config_path, config)?;
+
+        cmd!(
+            &self.command,
+            "supergraph",
+            "compose",
+            "--config",
+            &config_path,
+            "--elv2-license",
+            "accept"
+        )
+        .dir(dir)
+        .read()
+        .with_context(|| format!("Failed to compose with '{}'", self.command))
+    }
+}
+
+struct Wgc {
+    command: String,
+}
+
+impl Composer for Wgc {
+    fn compose(&self, subgraphs: &[SubgraphSdl], dir: &Path) -> Result<String> {
+        let mut config = String::from("version: 1\nsubgraphs:\n");
+        for subgraph in subgraphs {
+            config.push_str(&format!(
+                "  - name: {}\n    routing_url: {}\n    schema:\n      file: {}\n",
+                subgraph.name,
+                subgraph.url,
+                subgraph.path.display()
+            ));
+        }
+        let config_path = dir.join("compose.yml");
+        let output_path = dir.join("supergraph.json");
+        std::fs::write(&config_path, config)?;
+
+        cmd!(
+            &self.command,
+            "router",
+            "compose",
+            "-i",
+            &config_path,
+            "-o",
+            &output_path
+        )
+        .dir(dir)
+        .stdout_null()
+        .run()
+        .with_context(|| format!("Failed to compose with '{}'", self.command))?;
+
+        std::fs::read_to_string(&output_path)
+            .with_context(|| format!("Could not read {:?}", output_path))
+    }
+
+    /// The execution config has a random `version` on every composition.
+    fn normalize(&self, output: &str) -> Result<String> {
+        let mut config: serde_json::Value = serde_json::from_str(output)?;
+        if let Some(config) = config.as_object_mut() {
+            config.remove("version");
+        }
+        Ok(serde_json::to_string_pretty(&config)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wgc_normalize_should_ignore_the_version() {
+        let wgc = Wgc {
+            command: "wgc".to_string(),
+        };
+        let normalized = [
+            r#"{"engineConfig":{},"version":"726e233f"}"#,
+            r#"{"version": "0b1c2d3e", "engineConfig": {}}"#,
+        ]
+        .map(|output| wgc.normalize(output).unwrap());
+        insta::assert_debug_snapshot!(normalized, @r#"
+        [
+            "{\n  \"engineConfig\": {}\n}",
+            "{\n  \"engineConfig\": {}\n}",
+        ]
+        "#);
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context as _, Result};
-use fast_glob::glob_match;
+use itertools::Itertools;
 use serde::Deserialize;
 use std::{
     collections::{BTreeMap, HashMap},
@@ -7,14 +7,19 @@ use std::{
     sync::Arc,
 };
 
-use crate::docker::{self, ContainerId};
+use crate::{
+    composition::ComposeConfig,
+    docker,
+    gateway::GatewayInstance,
+    load::{LoadConfig, LoadRequest},
+};
 
 /// The merged configuration file structure
 #[derive(Debug, Deserialize)]
 struct TomlConfig {
     scenarios: BTreeMap<String, ScenarioConfig>,
     supergraphs: BTreeMap<String, SupergraphConfig>,
-    gateways: BTreeMap<String, GatewayConfig>,
+    gateways: BTreeMap<String, GatewayEntry>,
 }
 
 /// Central configuration for the entire benchmark repository
@@ -23,26 +28,38 @@ pub struct Config {
     pub supergraphs: BTreeMap<String, SupergraphConfig>,
     pub gateways: Vec<Arc<Gateway>>,
     pub current_dir: PathBuf,
+    /// Overrides applied on top of config.toml, recorded for the report.
+    pub overrides: Vec<Override>,
 }
 
 impl Config {
-    /// Load all configurations from the repository
-    pub fn load(current_dir: PathBuf) -> Result<Self> {
+    /// Load all configurations from the repository, applying the given overrides on top of
+    /// config.toml.
+    pub fn load(current_dir: PathBuf, overrides: Vec<Override>) -> Result<Self> {
         // Load the merged config file from root
         let config_path = current_dir.join("config.toml");
         let content = std::fs::read_to_string(&config_path)
             .context("Could not read config.toml from root directory")?;
-        let merged_config: TomlConfig =
+        let mut table: toml::Table =
             toml::from_str(&content).context("Could not parse config.toml")?;
+        // Any `GFB_*` variable is an override, so each one is logged to spot unintended ones.
+        for item in &overrides {
+            item.apply(&mut table)?;
+            tracing::info!("Overriding config.toml with {item}");
+        }
+        let merged_config: TomlConfig = toml::Value::Table(table)
+            .try_into()
+            .context("Could not parse config.toml with the overrides")?;
 
         // Convert gateways to the expected format
-        let gateways = build_all(&current_dir, merged_config.gateways, None)?;
+        let gateways = build_all(&current_dir, merged_config.gateways)?;
 
         Ok(Self {
             scenarios: merged_config.scenarios,
             supergraphs: merged_config.supergraphs,
             gateways,
             current_dir,
+            overrides,
         })
     }
 
@@ -58,15 +75,150 @@ impl Config {
             .ok_or_else(|| anyhow::anyhow!("Supergraph '{}' not found in config.toml", name))
     }
 
+    /// Get a gateway by name, `<name>@<version>` selecting a specific version of it.
     pub fn get_gateway(&self, name: &str) -> Result<Arc<Gateway>> {
-        self.gateways
+        let (name, version) = match name.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (name, None),
+        };
+        let gateway = self
+            .gateways
             .iter()
             .find(|g| g.name() == name)
             .cloned()
-            .ok_or_else(|| anyhow::anyhow!("Gateway '{}' not found in config.toml", name))
+            .ok_or_else(|| anyhow::anyhow!("Gateway '{}' not found in config.toml", name))?;
+
+        Ok(match version {
+            // A process or a local build only has the version it was built from.
+            Some(version) if gateway.config.command.is_some() || gateway.config.build.is_some() => {
+                return Err(anyhow::anyhow!(
+                    "Gateway '{}' runs a process or a local build, it can't be selected at version '{}'",
+                    name,
+                    version
+                ));
+            }
+            Some(version) => Arc::new(gateway.with_version(version)),
+            None => gateway,
+        })
     }
 }
 
+/// Prefix of the environment variables overriding config.toml values.
+const ENV_OVERRIDE_PREFIX: &str = "GFB_";
+
+/// A config.toml value overridden from the command line with `--set key.path=value` or with an
+/// environment variable like `GFB_SCENARIOS__QUERY__ENV__DELAY_MS=20`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub path: Vec<String>,
+    pub value: String,
+}
+
+impl Override {
+    /// Parse a `key.path=value` override.
+    pub fn parse(value: &str) -> Result<Self> {
+        let (path, value) = value.split_once('=').ok_or_else(|| {
+            anyhow::anyhow!("Invalid override '{}', expected key.path=value", value)
+        })?;
+        let path: Vec<String> = path.trim().split('.').map(str::to_string).collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            return Err(anyhow::anyhow!("Invalid override key '{}'", path.join(".")));
+        }
+
+        Ok(Self {
+            path,
+            value: value.to_string(),
+        })
+    }
+
+    /// Overrides from `GFB_*` environment variables. Path segments are separated by `__` and
+    /// matched case-insensitively against config.toml keys, `_` also matching `-`.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<Self> {
+        let mut overrides: Vec<Self> = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let path = key.strip_prefix(ENV_OVERRIDE_PREFIX)?;
+                Some(Self {
+                    path: path.split("__").map(str::to_string).collect(),
+                    value,
+                })
+            })
+            .filter(|item| item.path.iter().all(|segment| !segment.is_empty()))
+            .collect();
+        overrides.sort_by(|a, b| a.path.cmp(&b.path));
+        overrides
+    }
+
+    fn apply(&self, root: &mut toml::Table) -> Result<()> {
+        let (key, parents) = self
+            .path
+            .split_last()
+            .expect("override paths are never empty");
+        let mut table = root;
+
+        for (depth, segment) in parents.iter().enumerate() {
+            let existing = find_key(table, segment);
+            // Sections and their entries must exist, deeper tables like `env` may be created.
+            if existing.is_none() && depth < 2 {
+                return Err(self.unknown_key());
+            }
+            // New tables are named like all config.toml tables, in lower case.
+            let segment = existing.unwrap_or_else(|| segment.to_lowercase());
+            table = match table
+                .entry(segment)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            {
+                toml::Value::Table(table) => table,
+                _ => return Err(self.unknown_key()),
+            };
+        }
+
+        if parents.len() < 2 && find_key(table, key).is_none() {
+            return Err(self.unknown_key());
+        }
+        let key = find_key(table, key).unwrap_or_else(|| key.clone());
+
+        // Values are TOML values, except for strings which don't need to be quoted. New keys are
+        // strings unless they look like an array or an inline table.
+        let value = match table.get(&key) {
+            Some(toml::Value::String(_)) => toml::Value::String(self.value.clone()),
+            None if !self.value.trim_start().starts_with(['[', '{']) => {
+                toml::Value::String(self.value.clone())
+            }
+            _ => toml::from_str::<toml::Table>(&format!("value = {}", self.value))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Invalid TOML value '{}' for override '{}'",
+                        self.value,
+                        self
+                    )
+                })?,
+        };
+        table.insert(key, value);
+
+        Ok(())
+    }
+
+    fn unknown_key(&self) -> anyhow::Error {
+        anyhow::anyhow!("Override '{}' doesn't match any key in config.toml", self)
+    }
+}
+
+impl std::fmt::Display for Override {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.path.join("."), self.value)
+    }
+}
+
+/// The key matching a segment of an override, ignoring the case and treating `_` as `-`.
+fn find_key(table: &toml::Table, segment: &str) -> Option<String> {
+    let normalize = |key: &str| key.to_lowercase().replace('_', "-");
+    let segment = normalize(segment);
+    table.keys().find(|key| normalize(key) == segment).cloned()
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ScenarioConfig {
     pub supergraph: String,
@@ -74,22 +226,137 @@ pub struct ScenarioConfig {
     pub description: String,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Tags to select scenarios with `tag:<tag>`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Load generated by the cli, instead of the hand-written `k6.js` of the scenario.
+    pub load: Option<LoadConfig>,
+}
+
+impl ScenarioConfig {
+    /// Requests sent by the scenario, each one checked against its expected response.
+    pub fn requests(&self, scenario_path: &Path) -> Result<Vec<LoadRequest>> {
+        match &self.load {
+            Some(load) => load.requests(scenario_path),
+            None => {
+                let path = scenario_path.join("body.json");
+                let body = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {:?}", path))?;
+                Ok(vec![LoadRequest {
+                    operation: None,
+                    weight: 1,
+                    body,
+                    generators: Default::default(),
+                    expected_size: None,
+                    fixture_dir: scenario_path.to_path_buf(),
+                }])
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SupergraphConfig {
     pub subgraphs: Vec<String>,
+    /// Port on which the compose service serving the subgraphs listens.
+    pub port: u16,
+    /// Supergraph files generated from the subgraph schemas by the `compose` command.
+    #[serde(default)]
+    pub compose: Vec<ComposeConfig>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// A gateway as declared in `config.toml`. It may extend another gateway, inheriting all of its
+/// settings and only overriding some of them. Arguments and environment variables values can use
+/// the following placeholders:
+/// - `{gateway_dir}`: the directory of the gateway within the `gateways/` mount, named after the
+///   root gateway by default.
+/// - `{config}`: the `config` file within the `{gateway_dir}`.
+/// - `{supergraph}`: the supergraph schema.
+///
+/// Paths are the mount points within the container for image gateways, and host paths for
+/// process gateways.
+#[derive(Debug, Clone, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
+struct GatewayEntry {
+    extends: Option<String>,
+    label: Option<String>,
+    kind: Option<GatewayKind>,
+    image: Option<String>,
+    command: Option<String>,
+    dir: Option<String>,
+    config: Option<String>,
+    args: Option<Vec<String>>,
+    /// Merged with the environment variables of the parent.
+    #[serde(default)]
+    env: HashMap<String, String>,
+    tags: Option<Vec<String>>,
+    /// Image tags to benchmark instead of the one from `image`.
+    versions: Option<Vec<String>>,
+    build: Option<BuildConfig>,
+}
+
+impl GatewayEntry {
+    /// Apply the settings of `child` on top of this entry.
+    fn merge(mut self, child: &GatewayEntry) -> GatewayEntry {
+        self.extends = child.extends.clone();
+        self.label = child.label.clone().or(self.label);
+        self.kind = child.kind.or(self.kind);
+        self.image = child.image.clone().or(self.image);
+        self.command = child.command.clone().or(self.command);
+        self.dir = child.dir.clone().or(self.dir);
+        self.config = child.config.clone().or(self.config);
+        self.args = child.args.clone().or(self.args);
+        self.env
+            .extend(child.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.tags = child.tags.clone().or(self.tags);
+        self.versions = child.versions.clone().or(self.versions);
+        self.build = child.build.clone().or(self.build);
+        self
+    }
+}
+
+/// How a gateway is run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GatewayKind {
+    /// A docker container started from `image`.
+    #[default]
+    Image,
+    /// A `command` running directly on the host, typically a local release build.
+    Process,
+}
+
+/// Fully resolved gateway configuration
+#[derive(Debug, Clone)]
 pub struct GatewayConfig {
     pub label: String,
+    /// Docker image, empty for process gateways.
     pub image: String,
-    #[serde(default)]
+    /// Command run on the host instead of a container for process gateways.
+    pub command: Option<String>,
     pub args: Vec<String>,
-    #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Tags to select gateways with `tag:<tag>`
+    pub tags: Vec<String>,
+    /// Image tags to benchmark, each one as a separate gateway `<name>@<version>`.
+    pub versions: Vec<String>,
+    /// Image tag of a gateway expanded from `versions`.
+    pub version: Option<String>,
+    /// Build the image from a local source directory rather than pulling it.
+    pub build: Option<BuildConfig>,
+}
+
+/// Docker build of a gateway image, tagged as the gateway `image` once built. Builds are cached
+/// by the content hash of the context, Dockerfile and build arguments.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildConfig {
+    /// Build context, relative to the repository root.
+    pub context: PathBuf,
+    /// Dockerfile relative to the context, `Dockerfile` by default.
+    pub dockerfile: Option<PathBuf>,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
 }
 
 #[derive(Debug)]
@@ -108,7 +375,97 @@ impl Gateway {
         &self.config.label
     }
 
-    pub fn start_with_supergraph(&self, supergraph_path: &Path) -> Result<ContainerId> {
+    pub fn tags(&self) -> &[String] {
+        &self.config.tags
+    }
+
+    /// Name of the gateway in config.toml, without the version.
+    pub fn base_name(&self) -> &str {
+        self.name.split('@').next().unwrap_or(&self.name)
+    }
+
+    /// The gateways to benchmark for this entry, one for each declared version if any.
+    pub fn expand_versions(self: &Arc<Self>) -> Vec<Arc<Gateway>> {
+        if self.config.versions.is_empty() {
+            return vec![self.clone()];
+        }
+
+        self.config
+            .versions
+            .iter()
+            .map(|version| Arc::new(self.with_version(version)))
+            .collect()
+    }
+
+    /// This gateway running the image with the given tag.
+    pub fn with_version(&self, version: &str) -> Gateway {
+        Gateway {
+            name: format!("{}@{}", self.base_name(), version),
+            gateways_path: self.gateways_path.clone(),
+            config: GatewayConfig {
+                label: format!("{} {}", self.config.label, version),
+                image: format!("{}:{}", image_repository(&self.config.image), version),
+                versions: Vec::new(),
+                version: Some(version.to_string()),
+                ..self.config.clone()
+            },
+        }
+    }
+
+    pub fn start_with_supergraph(&self, supergraph_path: &Path) -> Result<GatewayInstance> {
+        match &self.config.command {
+            Some(command) => {
+                let args = self.process_args(supergraph_path);
+                let env = self.process_env(supergraph_path);
+                GatewayInstance::spawn(self.name(), command, &args, &env)
+            }
+            None => {
+                docker::run(&self.docker_run_args(supergraph_path)).map(GatewayInstance::Container)
+            }
+        }
+    }
+
+    /// Command line starting this gateway with the given supergraph, as it would be typed in a
+    /// shell.
+    pub fn command_line(&self, supergraph_path: &Path) -> String {
+        match &self.config.command {
+            Some(command) => docker::format_command(
+                self.process_env(supergraph_path)
+                    .iter()
+                    .sorted()
+                    .map(|(k, v)| (k.as_str(), v.as_str())),
+                command,
+                &self.process_args(supergraph_path),
+            ),
+            None => docker::format_command([], "docker", &self.docker_run_args(supergraph_path)),
+        }
+    }
+
+    /// Arguments of a process gateway, with the host path of the supergraph schema.
+    fn process_args(&self, supergraph_path: &Path) -> Vec<String> {
+        let schema = supergraph_path.join("schema.graphql");
+        self.config
+            .args
+            .iter()
+            .map(|arg| arg.replace(SUPERGRAPH_PLACEHOLDER, &schema.to_string_lossy()))
+            .collect()
+    }
+
+    /// Environment of a process gateway, with the host path of the supergraph schema.
+    fn process_env(&self, supergraph_path: &Path) -> HashMap<String, String> {
+        let schema = supergraph_path.join("schema.graphql");
+        self.config
+            .env
+            .iter()
+            .map(|(key, value)| {
+                let value = value.replace(SUPERGRAPH_PLACEHOLDER, &schema.to_string_lossy());
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Arguments of the `docker` command starting this gateway with the given supergraph.
+    pub fn docker_run_args(&self, supergraph_path: &Path) -> Vec<String> {
         let volumes = vec![
             (
                 self.gateways_path.to_string_lossy().to_string(),
@@ -120,9 +477,13 @@ impl Gateway {
             ),
         ];
 
-        docker::run(
+        docker::run_args(
             &self.config.image,
-            self.config.env.iter().map(|(k, v)| (k.clone(), v.clone())),
+            self.config
+                .env
+                .iter()
+                .sorted()
+                .map(|(k, v)| (k.clone(), v.clone())),
             volumes.into_iter(),
             self.config.args.clone().into_iter(),
         )
@@ -132,26 +493,429 @@ impl Gateway {
 /// Load gateways from the merged config structure
 fn build_all(
     current_dir: &Path,
-    gateways: BTreeMap<String, GatewayConfig>,
-    filter: Option<String>,
+    gateways: BTreeMap<String, GatewayEntry>,
 ) -> Result<Vec<Arc<Gateway>>> {
     let gateways_path = current_dir.join("gateways");
 
-    Ok(gateways
-        .into_iter()
-        .filter(|(name, _)| {
-            if let Some(ref filter) = filter {
-                glob_match(filter, name)
-            } else {
-                true
-            }
-        })
-        .map(|(name, config)| {
-            Arc::new(Gateway {
+    gateways
+        .keys()
+        .map(|name| {
+            let config = resolve_gateway(name, &gateways, &gateways_path)?;
+            Ok(Arc::new(Gateway {
                 gateways_path: gateways_path.clone(),
                 name: name.to_lowercase(),
                 config,
-            })
+            }))
         })
-        .collect())
+        .collect()
+}
+
+/// Placeholder of the supergraph schema path. For process gateways it's only substituted when
+/// starting the gateway, as the supergraph depends on the scenario.
+const SUPERGRAPH_PLACEHOLDER: &str = "{supergraph}";
+
+/// Resolve the inheritance chain of a gateway and its placeholders.
+fn resolve_gateway(
+    name: &str,
+    gateways: &BTreeMap<String, GatewayEntry>,
+    gateways_path: &Path,
+) -> Result<GatewayConfig> {
+    // Walk up the inheritance chain, from the gateway to its root ancestor.
+    let mut chain = vec![name];
+    let mut current = &gateways[name];
+    while let Some(parent) = &current.extends {
+        if chain.contains(&parent.as_str()) {
+            chain.push(parent);
+            return Err(anyhow::anyhow!(
+                "Gateway inheritance cycle in config.toml: {}",
+                chain.join(" -> ")
+            ));
+        }
+        current = gateways.get(parent).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Gateway '{}' extends unknown gateway '{}' in config.toml",
+                chain.last().expect("chain always contains the gateway"),
+                parent
+            )
+        })?;
+        chain.push(parent);
+    }
+
+    let root = *chain.last().expect("chain always contains the gateway");
+    let entry = chain
+        .iter()
+        .rev()
+        .fold(GatewayEntry::default(), |entry, name| {
+            entry.merge(&gateways[*name])
+        });
+
+    let label = entry
+        .label
+        .ok_or_else(|| anyhow::anyhow!("Gateway '{}' has no label in config.toml", name))?;
+    let dir = entry.dir.as_deref().unwrap_or(root);
+
+    let (image, command, gateway_dir, supergraph) = match entry.kind.unwrap_or_default() {
+        GatewayKind::Image => {
+            let image = entry
+                .image
+                .ok_or_else(|| anyhow::anyhow!("Gateway '{}' has no image in config.toml", name))?;
+            // Every version would be a tag of the same locally built image.
+            if entry.versions.is_some() && entry.build.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Gateway '{}' can't define both versions and a build in config.toml",
+                    name
+                ));
+            }
+            (
+                image,
+                None,
+                format!("/gateways/{dir}"),
+                "/supergraph/schema.graphql",
+            )
+        }
+        GatewayKind::Process => {
+            let command = entry.command.ok_or_else(|| {
+                anyhow::anyhow!("Process gateway '{}' has no command in config.toml", name)
+            })?;
+            if entry.versions.is_some() || entry.build.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Process gateway '{}' can't define versions or a build in config.toml",
+                    name
+                ));
+            }
+            (
+                String::new(),
+                Some(command),
+                gateways_path.join(dir).to_string_lossy().to_string(),
+                SUPERGRAPH_PLACEHOLDER,
+            )
+        }
+    };
+
+    let config = entry
+        .config
+        .as_ref()
+        .map(|config| format!("{gateway_dir}/{config}"));
+    let substitute = |value: &str| -> Result<String> {
+        let value = value
+            .replace("{gateway_dir}", &gateway_dir)
+            .replace(SUPERGRAPH_PLACEHOLDER, supergraph);
+        match &config {
+            Some(config) => Ok(value.replace("{config}", config)),
+            None if value.contains("{config}") => Err(anyhow::anyhow!(
+                "Gateway '{}' uses {{config}} without defining a config file in config.toml",
+                name
+            )),
+            None => Ok(value),
+        }
+    };
+
+    Ok(GatewayConfig {
+        label,
+        image,
+        command,
+        args: entry
+            .args
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| substitute(arg))
+            .collect::<Result<_>>()?,
+        env: entry
+            .env
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), substitute(value)?)))
+            .collect::<Result<_>>()?,
+        tags: entry.tags.unwrap_or_default(),
+        versions: entry.versions.unwrap_or_default(),
+        version: None,
+        build: entry.build,
+    })
+}
+
+/// Image reference without its tag, e.g. `ghcr.io/grafbase/gateway` for
+/// `ghcr.io/grafbase/gateway:0.49.1`. Registry ports aren't mistaken for tags.
+pub fn image_repository(image: &str) -> &str {
+    match image.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(config: &str, name: &str) -> Result<GatewayConfig> {
+        let gateways: BTreeMap<String, GatewayEntry> = toml::from_str(config).unwrap();
+        resolve_gateway(name, &gateways, Path::new("/repo/gateways"))
+    }
+
+    const CONFIG: &str = r#"
+        [grafbase]
+        label = "Grafbase Gateway"
+        image = "ghcr.io/grafbase/gateway:0.49.1"
+        config = "grafbase.toml"
+        args = ["--config", "{config}", "-s", "{supergraph}"]
+        env.RUST_LOG = "info"
+        tags = ["rust"]
+
+        [grafbase-no-cache]
+        extends = "grafbase"
+        label = "Grafbase Gateway (no cache)"
+        config = "no-cache.toml"
+
+        [grafbase-debug]
+        extends = "grafbase-no-cache"
+        label = "Grafbase Gateway (debug)"
+        env.RUST_LOG = "debug"
+        env.EXTRA = "{gateway_dir}/extra"
+
+        [grafbase-local]
+        extends = "grafbase"
+        label = "Grafbase Gateway (local)"
+        kind = "process"
+        command = "../grafbase/target/release/grafbase-gateway"
+        env.SUPERGRAPH = "{supergraph}"
+    "#;
+
+    /// Resolved configuration with its environment sorted, for stable snapshots.
+    fn describe(config: &GatewayConfig) -> String {
+        let env: BTreeMap<_, _> = config.env.iter().collect();
+        format!(
+            "label: {}\nimage: {:?}\ncommand: {:?}\nargs: {:?}\nenv: {env:?}\ntags: {:?}",
+            config.label, config.image, config.command, config.args, config.tags
+        )
+    }
+
+    #[test]
+    fn gateway_should_inherit_from_the_one_it_extends() {
+        let gateway = resolve(CONFIG, "grafbase-no-cache").unwrap();
+        insta::assert_snapshot!(describe(&gateway), @r#"
+        label: Grafbase Gateway (no cache)
+        image: "ghcr.io/grafbase/gateway:0.49.1"
+        command: None
+        args: ["--config", "/gateways/grafbase/no-cache.toml", "-s", "/supergraph/schema.graphql"]
+        env: {"RUST_LOG": "info"}
+        tags: ["rust"]
+        "#);
+
+        let gateway = resolve(CONFIG, "grafbase-debug").unwrap();
+        insta::assert_snapshot!(describe(&gateway), @r#"
+        label: Grafbase Gateway (debug)
+        image: "ghcr.io/grafbase/gateway:0.49.1"
+        command: None
+        args: ["--config", "/gateways/grafbase/no-cache.toml", "-s", "/supergraph/schema.graphql"]
+        env: {"EXTRA": "/gateways/grafbase/extra", "RUST_LOG": "debug"}
+        tags: ["rust"]
+        "#);
+    }
+
+    #[test]
+    fn process_gateway_should_use_host_paths() {
+        let config = resolve(CONFIG, "grafbase-local").unwrap();
+        insta::assert_snapshot!(describe(&config), @r#"
+        label: Grafbase Gateway (local)
+        image: ""
+        command: Some("../grafbase/target/release/grafbase-gateway")
+        args: ["--config", "/repo/gateways/grafbase/grafbase.toml", "-s", "{supergraph}"]
+        env: {"RUST_LOG": "info", "SUPERGRAPH": "{supergraph}"}
+        tags: ["rust"]
+        "#);
+
+        let gateway = Gateway {
+            name: "grafbase-local".to_string(),
+            gateways_path: PathBuf::from("/repo/gateways"),
+            config,
+        };
+        let command_line = gateway.command_line(Path::new("/repo/supergraphs/fed"));
+        insta::assert_snapshot!(command_line, @"RUST_LOG=info SUPERGRAPH=/repo/supergraphs/fed/schema.graphql ../grafbase/target/release/grafbase-gateway --config /repo/gateways/grafbase/grafbase.toml -s /repo/supergraphs/fed/schema.graphql");
+
+        let err = resolve(
+            r#"
+            [a]
+            label = "A"
+            kind = "process"
+            "#,
+            "a",
+        )
+        .unwrap_err();
+        insta::assert_snapshot!(err, @"Process gateway 'a' has no command in config.toml");
+    }
+
+    #[test]
+    fn must_reject_invalid_gateway_inheritance() {
+        let errors = [
+            r#"
+            [a]
+            extends = "b"
+            [b]
+            extends = "a"
+            "#,
+            r#"
+            [a]
+            extends = "unknown"
+            "#,
+            r#"
+            [a]
+            label = "A"
+            image = "a"
+            args = ["{config}"]
+            "#,
+        ]
+        .map(|config| resolve(config, "a").unwrap_err().to_string());
+        insta::assert_debug_snapshot!(errors, @r#"
+        [
+            "Gateway inheritance cycle in config.toml: a -> b -> a",
+            "Gateway 'a' extends unknown gateway 'unknown' in config.toml",
+            "Gateway 'a' uses {config} without defining a config file in config.toml",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn overrides_should_replace_existing_keys_only() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [gateways.grafbase]
+            image = "ghcr.io/grafbase/gateway:0.49.1"
+
+            [scenarios.query]
+            supergraph = "fed"
+            env.DELAY_MS = "10"
+
+            [scenarios.big-response]
+            supergraph = "big-response"
+
+            [supergraphs.fed]
+            port = 7200
+            "#,
+        )
+        .unwrap();
+
+        let overrides = [
+            Override::parse("gateways.grafbase.image=grafbase:main").unwrap(),
+            Override::parse("gateways.grafbase.versions=[\"0.48.0\"]").unwrap(),
+            Override::parse("supergraphs.fed.port=7300").unwrap(),
+        ]
+        .into_iter()
+        .chain(Override::from_env([
+            (
+                "GFB_SCENARIOS__QUERY__ENV__DELAY_MS".to_string(),
+                "20".to_string(),
+            ),
+            (
+                "GFB_SCENARIOS__BIG_RESPONSE__ENV__SUB1_DELAY_MS".to_string(),
+                "100".to_string(),
+            ),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]));
+        for item in overrides {
+            item.apply(&mut table).unwrap();
+        }
+        insta::assert_snapshot!(toml::to_string(&table).unwrap(), @r#"
+        [gateways.grafbase]
+        image = "grafbase:main"
+        versions = ["0.48.0"]
+
+        [scenarios.big-response]
+        supergraph = "big-response"
+
+        [scenarios.big-response.env]
+        SUB1_DELAY_MS = "100"
+
+        [scenarios.query]
+        supergraph = "fed"
+
+        [scenarios.query.env]
+        DELAY_MS = "20"
+
+        [supergraphs.fed]
+        port = 7300
+        "#);
+
+        let err = Override::parse("scenarios.unknown.env.DELAY_MS=1")
+            .unwrap()
+            .apply(&mut table)
+            .unwrap_err();
+        insta::assert_snapshot!(err, @"Override 'scenarios.unknown.env.DELAY_MS=1' doesn't match any key in config.toml");
+        let err = Override::parse("supergraphs.fed.port=abc")
+            .unwrap()
+            .apply(&mut table)
+            .unwrap_err();
+        insta::assert_snapshot!(err, @"Invalid TOML value 'abc' for override 'supergraphs.fed.port=abc'");
+        let err = Override::parse("gateways.grafbase.image").unwrap_err();
+        insta::assert_snapshot!(err, @"Invalid override 'gateways.grafbase.image', expected key.path=value");
+    }
+
+    #[test]
+    fn versions_should_expand_into_one_gateway_per_image_tag() {
+        let mut config = resolve(CONFIG, "grafbase").unwrap();
+        config.versions = vec!["0.48.0".to_string(), "main".to_string()];
+        let gateway = Arc::new(Gateway {
+            name: "grafbase".to_string(),
+            gateways_path: PathBuf::from("/test/gateways"),
+            config,
+        });
+
+        let versions: Vec<String> = gateway
+            .expand_versions()
+            .iter()
+            .map(|version| {
+                format!(
+                    "{} ({}): {}, {}, version {:?}",
+                    version.name(),
+                    version.base_name(),
+                    version.label(),
+                    version.config.image,
+                    version.config.version
+                )
+            })
+            .collect();
+        insta::assert_snapshot!(versions.join("\n"), @r#"
+        grafbase@0.48.0 (grafbase): Grafbase Gateway 0.48.0, ghcr.io/grafbase/gateway:0.48.0, version Some("0.48.0")
+        grafbase@main (grafbase): Grafbase Gateway main, ghcr.io/grafbase/gateway:main, version Some("main")
+        "#);
+
+        let repositories = [
+            "localhost:5000/gateway",
+            "localhost:5000/gateway:1.0",
+            "apollo-router-no-cache",
+        ]
+        .map(image_repository);
+        insta::assert_debug_snapshot!(repositories, @r#"
+        [
+            "localhost:5000/gateway",
+            "localhost:5000/gateway",
+            "apollo-router-no-cache",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn versions_must_not_apply_to_processes_nor_local_builds() {
+        let err = resolve(
+            r#"
+            [a]
+            label = "A"
+            image = "a"
+            versions = ["1.0", "2.0"]
+            build = { context = "builds/a" }
+            "#,
+            "a",
+        )
+        .unwrap_err();
+        insta::assert_snapshot!(err, @"Gateway 'a' can't define both versions and a build in config.toml");
+
+        let gateways: BTreeMap<String, GatewayEntry> = toml::from_str(CONFIG).unwrap();
+        let config = Config {
+            scenarios: BTreeMap::new(),
+            supergraphs: BTreeMap::new(),
+            gateways: build_all(Path::new("/repo"), gateways).unwrap(),
+            current_dir: PathBuf::from("/repo"),
+            overrides: Vec::new(),
+        };
+        assert!(config.get_gateway("grafbase@0.48.0").is_ok());
+        let err = config.get_gateway("grafbase-local@0.48.0").unwrap_err();
+        insta::assert_snapshot!(err, @"Gateway 'grafbase-local' runs a process or a local build, it can't be selected at version '0.48.0'");
+    }
 }
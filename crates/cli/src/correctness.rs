@@ -0,0 +1,383 @@
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::{gateway::GATEWAY_PORT, load::LoadRequest};
+
+/// Expected response of a scenario, compared to the one of each gateway before the load starts.
+pub enum Expected {
+    /// `expected.json`, compared structurally without taking the order of the keys into account.
+    Response(Value),
+    /// `expected-hash.json`, for responses too big to be committed.
+    Hash(ResponseHash),
+}
+
+/// Hash and size of the canonical JSON of a response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseHash {
+    pub hash: String,
+    pub size: usize,
+    /// Size of the reference response as it was received, which the load compares the size of
+    /// the responses with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_size: Option<usize>,
+}
+
+impl ResponseHash {
+    pub fn of(value: &Value) -> Self {
+        let canonical = canonical_json(value);
+        Self {
+            hash: format!("{:016x}", rapidhash::v3::rapidhash_v3(canonical.as_bytes())),
+            size: canonical.len(),
+            response_size: None,
+        }
+    }
+}
+
+/// Correctness verdict of the responses of a gateway.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Correctness {
+    /// Number of differences with the expected response.
+    pub differences: usize,
+    /// First differences found, e.g. `$.data.users[0].name: expected "a", got "b"`.
+    pub excerpt: Vec<String>,
+}
+
+impl Correctness {
+    pub fn is_correct(&self) -> bool {
+        self.differences == 0
+    }
+
+    fn record(&mut self, difference: String) {
+        self.differences += 1;
+        if self.excerpt.len() < EXCERPT_SIZE {
+            self.excerpt.push(difference);
+        }
+    }
+
+    /// Add the verdict of the response of an operation of a workload mix, or of the single
+    /// request of the scenario.
+    fn merge(&mut self, operation: Option<&str>, other: Correctness) {
+        self.differences += other.differences;
+        for difference in other.excerpt {
+            if self.excerpt.len() < EXCERPT_SIZE {
+                self.excerpt.push(match operation {
+                    Some(operation) => format!("{operation}: {difference}"),
+                    None => difference,
+                });
+            }
+        }
+    }
+
+    fn incorrect(message: String) -> Self {
+        Self {
+            differences: 1,
+            excerpt: vec![message],
+        }
+    }
+}
+
+/// Maximum number of differences kept in the excerpt.
+const EXCERPT_SIZE: usize = 5;
+
+/// Largest response committed as `expected.json`, bigger ones are stored as a hash.
+const MAX_RESPONSE_FIXTURE_SIZE: usize = 1024 * 1024;
+
+impl Expected {
+    /// Fixture of a reference response, received with the given size.
+    pub fn from_response(response: Value, response_size: usize) -> Self {
+        let hash = ResponseHash::of(&response);
+        if hash.size > MAX_RESPONSE_FIXTURE_SIZE {
+            Self::Hash(ResponseHash {
+                response_size: Some(response_size),
+                ..hash
+            })
+        } else {
+            Self::Response(response)
+        }
+    }
+
+    /// File storing this fixture within the scenario directory.
+    fn file_name(&self) -> &'static str {
+        match self {
+            Self::Response(_) => "expected.json",
+            Self::Hash(_) => "expected-hash.json",
+        }
+    }
+
+    /// Write the fixture into the scenario directory, replacing the previous one.
+    pub fn write(&self, scenario_path: &Path) -> Result<()> {
+        for file_name in ["expected.json", "expected-hash.json"] {
+            let path = scenario_path.join(file_name);
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+        }
+
+        // The generated load script compares the size of the responses with `expected.json`, so
+        // it's kept compact. Exact validations compare the parsed JSON, whatever the key order.
+        let content = match self {
+            Self::Response(response) => canonical_json(response),
+            Self::Hash(hash) => serde_json::to_string_pretty(hash)? + "\n",
+        };
+        let path = scenario_path.join(self.file_name());
+        std::fs::write(&path, content).with_context(|| format!("Could not write {:?}", path))
+    }
+
+    /// Size of the responses checked by the load when the fixture is only a hash.
+    pub fn response_size(&self) -> Option<usize> {
+        match self {
+            Self::Response(_) => None,
+            Self::Hash(hash) => hash.response_size,
+        }
+    }
+
+    /// Load the expected response of a scenario, if it has any.
+    pub fn load(scenario_path: &Path) -> Result<Option<Self>> {
+        let response_path = scenario_path.join("expected.json");
+        if response_path.exists() {
+            let content = std::fs::read_to_string(&response_path)?;
+            let value = serde_json::from_str(&content)
+                .with_context(|| format!("Could not parse {:?}", response_path))?;
+            return Ok(Some(Self::Response(value)));
+        }
+
+        let hash_path = scenario_path.join("expected-hash.json");
+        if hash_path.exists() {
+            let content = std::fs::read_to_string(&hash_path)?;
+            let hash = serde_json::from_str(&content)
+                .with_context(|| format!("Could not parse {:?}", hash_path))?;
+            return Ok(Some(Self::Hash(hash)));
+        }
+
+        Ok(None)
+    }
+
+    /// Compare the response of a gateway to the expected one.
+    pub fn check(&self, status: u16, body: &[u8]) -> Correctness {
+        if status != 200 {
+            return Correctness::incorrect(format!("HTTP status {status}"));
+        }
+        let actual: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(e) => return Correctness::incorrect(format!("response is not valid JSON: {e}")),
+        };
+
+        match self {
+            Self::Response(expected) => {
+                let mut correctness = Correctness::default();
+                diff(&mut correctness, "$", expected, &actual);
+                correctness
+            }
+            Self::Hash(expected) => {
+                let hash = ResponseHash::of(&actual);
+                if hash.hash == expected.hash && hash.size == expected.size {
+                    Correctness::default()
+                } else {
+                    Correctness::incorrect(format!(
+                        "expected hash {} ({} bytes), got {} ({} bytes)",
+                        expected.hash, expected.size, hash.hash, hash.size
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Send each request of the scenario once to the gateway and check the responses of those with
+/// an expected one. `None` if none of them has any.
+pub async fn check_gateway(requests: &[LoadRequest]) -> Result<Option<Correctness>> {
+    let mut correctness: Option<Correctness> = None;
+    for request in requests.iter().filter(|request| request.is_static()) {
+        let Some(expected) = Expected::load(&request.fixture_dir)? else {
+            continue;
+        };
+        let (status, body) = send_request(
+            &format!("http://localhost:{GATEWAY_PORT}/graphql"),
+            request.body.clone(),
+        )
+        .await?;
+        correctness
+            .get_or_insert_default()
+            .merge(request.operation.as_deref(), expected.check(status, &body));
+    }
+    Ok(correctness)
+}
+
+/// Send a GraphQL request, returning the status and body of the response.
+pub async fn send_request(url: &str, body: String) -> Result<(u16, Vec<u8>)> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("Authorization", "Bearer correctness-check")
+        .body(body)
+        .send()
+        .await?;
+    let status = response.status().as_u16();
+    let body = response.bytes().await?;
+
+    Ok((status, body.to_vec()))
+}
+
+/// JSON with sorted keys and no whitespace, identical for any key ordering.
+pub fn canonical_json(value: &Value) -> String {
+    let mut output = String::new();
+    write_canonical(&mut output, value);
+    output
+}
+
+fn write_canonical(output: &mut String, value: &Value) {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.push('{');
+            for (idx, (key, value)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    output.push(',');
+                }
+                output.push_str(&Value::String(key.clone()).to_string());
+                output.push(':');
+                write_canonical(output, value);
+            }
+            output.push('}');
+        }
+        Value::Array(array) => {
+            output.push('[');
+            for (idx, value) in array.iter().enumerate() {
+                if idx > 0 {
+                    output.push(',');
+                }
+                write_canonical(output, value);
+            }
+            output.push(']');
+        }
+        value => output.push_str(&value.to_string()),
+    }
+}
+
+fn diff(correctness: &mut Correctness, path: &str, expected: &Value, actual: &Value) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected {
+                let key_path = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(actual_value) => {
+                        diff(correctness, &key_path, expected_value, actual_value)
+                    }
+                    None => correctness.record(format!("{key_path}: missing")),
+                }
+            }
+            for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                correctness.record(format!("{path}.{key}: unexpected"));
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                correctness.record(format!(
+                    "{path}: expected {} items, got {}",
+                    expected.len(),
+                    actual.len()
+                ));
+            }
+            for (idx, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(correctness, &format!("{path}[{idx}]"), expected, actual);
+            }
+        }
+        (Value::Number(expected), Value::Number(actual))
+            if expected.as_f64() == actual.as_f64() => {}
+        (expected, actual) if expected == actual => {}
+        (expected, actual) => correctness.record(format!(
+            "{path}: expected {}, got {}",
+            excerpt_value(expected),
+            excerpt_value(actual)
+        )),
+    }
+}
+
+/// Short representation of a value for the diff excerpt.
+fn excerpt_value(value: &Value) -> String {
+    const MAX_LENGTH: usize = 80;
+    let value = value.to_string();
+    match value.char_indices().nth(MAX_LENGTH) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn check_should_ignore_key_order() {
+        let expected = Expected::Response(json!({"data": {"a": 1, "b": [{"c": "x", "d": 2.0}]}}));
+
+        let correctness = expected.check(200, br#"{"data":{"b":[{"d":2,"c":"x"}],"a":1}}"#);
+        assert!(correctness.is_correct());
+
+        let correctness = expected.check(200, br#"{"data":{"b":[{"c":"y"},{}],"e":null}}"#);
+        insta::assert_debug_snapshot!((correctness.differences, correctness.excerpt), @r#"
+        (
+            5,
+            [
+                "$.data.a: missing",
+                "$.data.b: expected 1 items, got 2",
+                "$.data.b[0].c: expected \"x\", got \"y\"",
+                "$.data.b[0].d: missing",
+                "$.data.e: unexpected",
+            ],
+        )
+        "#);
+
+        let correctness = expected.check(500, b"");
+        insta::assert_debug_snapshot!(correctness.excerpt, @r#"
+        [
+            "HTTP status 500",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn hash_check_should_only_accept_identical_canonical_responses() {
+        let response = json!({"data": {"b": [1, 2], "a": "x"}});
+        let expected = Expected::Hash(ResponseHash::of(&response));
+        insta::assert_snapshot!(canonical_json(&response), @r#"{"data":{"a":"x","b":[1,2]}}"#);
+
+        let checks = [
+            br#"{"data":{"a":"x","b":[1,2]}}"#,
+            br#"{"data":{"a":"x","b":[2,1]}}"#,
+        ]
+        .map(|body| expected.check(200, body).is_correct());
+        insta::assert_debug_snapshot!(checks, @r#"
+        [
+            true,
+            false,
+        ]
+        "#);
+    }
+
+    #[test]
+    fn written_fixture_should_replace_the_hash_and_accept_the_response() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("expected-hash.json"), "{}").unwrap();
+
+        let response = json!({"data": {"b": 1, "a": "x"}});
+        Expected::from_response(response.clone(), 24)
+            .write(dir)
+            .unwrap();
+        assert!(!dir.join("expected-hash.json").exists());
+        let fixture = std::fs::read_to_string(dir.join("expected.json")).unwrap();
+        insta::assert_snapshot!(fixture, @r#"{"data":{"a":"x","b":1}}"#);
+
+        let expected = Expected::load(dir).unwrap().unwrap();
+        assert!(
+            expected
+                .check(200, response.to_string().as_bytes())
+                .is_correct()
+        );
+    }
+}
@@ -1,6 +1,20 @@
 use anyhow::Result;
+use bollard::{
+    Docker,
+    query_parameters::{
+        ListContainersOptionsBuilder, RemoveContainerOptionsBuilder, StopContainerOptionsBuilder,
+    },
+};
 use duct::cmd;
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    time::Duration,
+};
+
+/// Label attached to every container started by the cli, gateways and compose services alike.
+/// It allows us to clean up after ourselves without touching any other container.
+pub const LABEL: &str = "com.grafbase.federation-benchmarks";
 
 pub fn compose_up(path: &Path, services: &[String], env: &HashMap<String, String>) -> Result<()> {
     if services.is_empty() {
@@ -13,17 +27,7 @@ pub fn compose_up(path: &Path, services: &[String], env: &HashMap<String, String
         path
     );
 
-    let mut args = vec![
-        "compose".to_string(),
-        "up".to_string(),
-        "-d".to_string(),
-        "--wait".to_string(),
-        "--build".to_string(),
-        "--force-recreate".to_string(),
-    ];
-    args.extend(services.iter().cloned());
-
-    let mut docker_cmd = cmd("docker", &args);
+    let mut docker_cmd = cmd("docker", compose_up_args(services));
 
     // Add environment variables
     for (key, value) in env {
@@ -40,6 +44,20 @@ pub fn compose_up(path: &Path, services: &[String], env: &HashMap<String, String
     Ok(())
 }
 
+/// Arguments of the `docker` command starting the compose services.
+pub fn compose_up_args(services: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "compose".to_string(),
+        "up".to_string(),
+        "-d".to_string(),
+        "--wait".to_string(),
+        "--build".to_string(),
+        "--force-recreate".to_string(),
+    ];
+    args.extend(services.iter().cloned());
+    args
+}
+
 pub fn compose_down(path: &Path) -> Result<()> {
     tracing::debug!("Stopping subgraphs with docker compose at {:?}", path);
 
@@ -62,17 +80,20 @@ impl std::ops::Deref for ContainerId {
     }
 }
 
-pub fn run(
+/// Arguments of the `docker` command starting a gateway container.
+pub fn run_args(
     image: &str,
     env: impl Iterator<Item = (String, String)>,
     volumes: impl Iterator<Item = (String, String)>,
     arguments: impl Iterator<Item = String>,
-) -> Result<ContainerId> {
+) -> Vec<String> {
     let mut args = vec![
         "run".to_string(),
         "-d".to_string(),
         "--network".to_string(),
         "host".to_string(),
+        "--label".to_string(),
+        format!("{LABEL}=gateway"),
     ];
 
     for (host_dir, guest_dir) in volumes {
@@ -87,10 +108,13 @@ pub fn run(
 
     args.push(image.to_string());
     args.extend(arguments);
+    args
+}
 
+pub fn run(args: &[String]) -> Result<ContainerId> {
     tracing::debug!("docker {}", args.join(" "));
 
-    let out = cmd("docker", &args)
+    let out = cmd("docker", args)
         .read()
         .map_err(|e| anyhow::anyhow!("Failed to start gateway container: {}", e))?;
 
@@ -100,6 +124,87 @@ pub fn run(
     Ok(ContainerId(id))
 }
 
+/// Arguments of the `docker` command building an image.
+pub fn build_args(
+    tag: &str,
+    context: &Path,
+    dockerfile: Option<&Path>,
+    build_args: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut args = vec![
+        "build".to_string(),
+        "-t".to_string(),
+        tag.to_string(),
+        "--label".to_string(),
+        format!("{LABEL}=image"),
+    ];
+
+    if let Some(dockerfile) = dockerfile {
+        args.push("-f".to_string());
+        args.push(context.join(dockerfile).to_string_lossy().to_string());
+    }
+
+    for (key, value) in build_args {
+        args.push("--build-arg".to_string());
+        args.push(format!("{}={}", key, value));
+    }
+
+    args.push(context.to_string_lossy().to_string());
+    args
+}
+
+pub fn build(args: &[String]) -> Result<()> {
+    tracing::debug!("docker {}", args.join(" "));
+
+    cmd("docker", args)
+        .run()
+        .map_err(|e| anyhow::anyhow!("Failed to build image: {}", e))?;
+
+    Ok(())
+}
+
+pub fn image_exists(image: &str) -> bool {
+    cmd!("docker", "image", "inspect", image)
+        .stdout_null()
+        .stderr_null()
+        .unchecked()
+        .run()
+        .is_ok_and(|output| output.status.success())
+}
+
+pub fn tag(source: &str, target: &str) -> Result<()> {
+    cmd!("docker", "tag", source, target)
+        .run()
+        .map_err(|e| anyhow::anyhow!("Failed to tag image {} as {}: {}", source, target, e))?;
+
+    Ok(())
+}
+
+/// Format a command line so that it can be copy-pasted into a shell.
+pub fn format_command<'a>(
+    env: impl IntoIterator<Item = (&'a str, &'a str)>,
+    program: &str,
+    args: &[String],
+) -> String {
+    fn quote(s: &str) -> String {
+        if !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@+".contains(c))
+        {
+            s.to_string()
+        } else {
+            format!("'{}'", s.replace('\'', "'\\''"))
+        }
+    }
+
+    env.into_iter()
+        .map(|(key, value)| format!("{}={}", key, quote(value)))
+        .chain(std::iter::once(program.to_string()))
+        .chain(args.iter().map(|arg| quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub fn stop(container_id: &str) -> Result<()> {
     tracing::debug!("Stopping container: {}", container_id);
 
@@ -119,3 +224,78 @@ pub fn stop(container_id: &str) -> Result<()> {
     tracing::debug!("Container stopped and removed");
     Ok(())
 }
+
+/// Stop and remove all containers started by the cli and ensure the given ports are free.
+pub async fn cleanup(docker: &Docker, ports: &[u16]) -> Result<()> {
+    let filters = HashMap::from([("label", vec![LABEL])]);
+    let containers = docker
+        .list_containers(Some(
+            ListContainersOptionsBuilder::new()
+                .all(true)
+                .filters(&filters)
+                .build(),
+        ))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list containers: {}", e))?;
+
+    for container in containers {
+        let Some(id) = container.id else {
+            continue;
+        };
+        tracing::debug!("Removing container {} ({:?})", id, container.names);
+
+        // Stopping fails if the container isn't running anymore, which is fine.
+        if let Err(e) = docker
+            .stop_container(&id, Some(StopContainerOptionsBuilder::new().t(2).build()))
+            .await
+        {
+            tracing::debug!("Could not stop container {}: {}", id, e);
+        }
+
+        docker
+            .remove_container(
+                &id,
+                Some(
+                    RemoveContainerOptionsBuilder::new()
+                        .force(true)
+                        .v(true)
+                        .build(),
+                ),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to remove container {}: {}", id, e))?;
+    }
+
+    wait_for_ports_to_be_free(ports).await
+}
+
+async fn wait_for_ports_to_be_free(ports: &[u16]) -> Result<()> {
+    const WAIT_DURATION: Duration = Duration::from_secs(10);
+
+    let start = std::time::Instant::now();
+    loop {
+        let mut ports_in_use = Vec::new();
+        for &port in ports {
+            // If we can connect, something is still listening on it.
+            if tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .is_ok()
+            {
+                ports_in_use.push(port);
+            }
+        }
+
+        if ports_in_use.is_empty() {
+            return Ok(());
+        }
+
+        if start.elapsed() > WAIT_DURATION {
+            return Err(anyhow::anyhow!(
+                "Ports {:?} are still in use after cleanup, is something else running?",
+                ports_in_use
+            ));
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
@@ -1,6 +1,147 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use std::{
+    collections::HashMap,
+    os::unix::process::CommandExt as _,
+    path::PathBuf,
+    process::{Child, ExitStatus, Stdio},
+    time::Duration,
+};
 
-pub async fn wait_for_gateway_health_with_logs(container_id: &str) -> Result<()> {
+use crate::docker::{self, ContainerId};
+
+/// Port every gateway is expected to listen on.
+pub const GATEWAY_PORT: u16 = 4000;
+
+/// Number of log lines of a process gateway shown when it exits unexpectedly.
+const LOG_TAIL_LINES: usize = 20;
+
+/// Time given to a process gateway to shut down gracefully before being killed.
+const PROCESS_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A running gateway, either a docker container or a process on the host.
+pub enum GatewayInstance {
+    Container(ContainerId),
+    Process(GatewayProcess),
+}
+
+pub struct GatewayProcess {
+    child: Child,
+    log_path: PathBuf,
+}
+
+impl GatewayInstance {
+    /// Start a process gateway in its own process group, so that it can be stopped with all of
+    /// its children. Its output is written to a log file.
+    pub fn spawn(
+        name: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let log_path = std::env::temp_dir().join(format!("federation-benchmarks-{name}.log"));
+        let log_file = std::fs::File::create(&log_path)
+            .with_context(|| format!("Could not create log file {:?}", log_path))?;
+
+        tracing::debug!("{} {}", command, args.join(" "));
+        let child = std::process::Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(log_file.try_clone()?)
+            .stderr(log_file)
+            .process_group(0)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start gateway process '{}': {}", command, e))?;
+        tracing::debug!("Gateway process started with PID: {}", child.id());
+
+        Ok(Self::Process(GatewayProcess { child, log_path }))
+    }
+
+    /// PID of the process gateway, also its process group ID.
+    pub fn pid(&self) -> Option<u32> {
+        match self {
+            Self::Container(_) => None,
+            Self::Process(process) => Some(process.child.id()),
+        }
+    }
+
+    /// Exit status of a process gateway which already exited, always `None` for containers.
+    fn exit_status(&mut self) -> Result<Option<ExitStatus>> {
+        match self {
+            Self::Container(_) => Ok(None),
+            Self::Process(process) => Ok(process.child.try_wait()?),
+        }
+    }
+
+    /// Last lines of the output of a process gateway.
+    fn log_tail(&self) -> String {
+        match self {
+            Self::Container(_) => String::new(),
+            Self::Process(process) => {
+                let log = std::fs::read_to_string(&process.log_path).unwrap_or_default();
+                let lines: Vec<&str> = log.lines().collect();
+                lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+            }
+        }
+    }
+
+    /// Command following the gateway logs.
+    fn log_command(&self) -> (&'static str, Vec<String>) {
+        match self {
+            Self::Container(container_id) => (
+                "docker",
+                vec![
+                    "logs".to_string(),
+                    "-f".to_string(),
+                    container_id.to_string(),
+                ],
+            ),
+            Self::Process(process) => (
+                "tail",
+                vec![
+                    "-n".to_string(),
+                    "+1".to_string(),
+                    "-f".to_string(),
+                    process.log_path.to_string_lossy().to_string(),
+                ],
+            ),
+        }
+    }
+
+    pub fn stop(self) -> Result<()> {
+        match self {
+            Self::Container(container_id) => docker::stop(&container_id),
+            Self::Process(mut process) => {
+                let pid = process.child.id();
+                tracing::debug!("Stopping gateway process group: {}", pid);
+
+                let group = format!("-{pid}");
+                let _ = duct::cmd!("kill", "-TERM", "--", &group)
+                    .stderr_null()
+                    .unchecked()
+                    .run();
+
+                let start = std::time::Instant::now();
+                while process.child.try_wait()?.is_none() {
+                    if start.elapsed() > PROCESS_STOP_TIMEOUT {
+                        let _ = duct::cmd!("kill", "-KILL", "--", &group)
+                            .stderr_null()
+                            .unchecked()
+                            .run();
+                        process.child.wait()?;
+                        break;
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+
+                tracing::debug!("Gateway process stopped");
+                Ok(())
+            }
+        }
+    }
+}
+
+pub async fn wait_for_gateway_health_with_logs(gateway: &mut GatewayInstance) -> Result<()> {
     use tokio::io::{AsyncBufReadExt, BufReader};
     use tokio::process::Command;
 
@@ -12,8 +153,9 @@ pub async fn wait_for_gateway_health_with_logs(container_id: &str) -> Result<()>
     tracing::info!("Waiting for gateway to be healthy...");
 
     // Start log streaming process
-    let mut log_process = Command::new("docker")
-        .args(["logs", "-f", container_id])
+    let (program, args) = gateway.log_command();
+    let mut log_process = Command::new(program)
+        .args(args)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .spawn()?;
@@ -53,8 +195,21 @@ pub async fn wait_for_gateway_health_with_logs(container_id: &str) -> Result<()>
     // Health check loop
     let start = std::time::Instant::now();
     while start.elapsed().as_secs() < WAIT_DURATION_S {
+        // A process gateway which exited will never become healthy.
+        if let Some(status) = gateway.exit_status()? {
+            let _ = log_tx.send(());
+            let _ = log_process.kill().await;
+            let _ = log_handle.await;
+
+            return Err(anyhow::anyhow!(
+                "Gateway process exited with {} before becoming healthy:\n{}",
+                status,
+                gateway.log_tail()
+            ));
+        }
+
         if let Ok(response) = client
-            .post("http://localhost:4000/graphql")
+            .post(format!("http://localhost:{GATEWAY_PORT}/graphql"))
             .header("Content-Type", "application/json")
             .body(health_query)
             .send()
@@ -87,3 +242,68 @@ pub async fn wait_for_gateway_health_with_logs(container_id: &str) -> Result<()>
         WAIT_DURATION_S
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopping_a_process_gateway_must_kill_its_process_group() {
+        let gateway = GatewayInstance::spawn(
+            "test-process-group",
+            "sh",
+            &[
+                "-c".to_string(),
+                "sleep 30 & echo started; wait".to_string(),
+            ],
+            &HashMap::new(),
+        )
+        .unwrap();
+        let pid = gateway.pid().unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let (program, args) = gateway.log_command();
+        assert_eq!(program, "tail");
+        let log_path = PathBuf::from(args.last().unwrap());
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "started\n");
+
+        gateway.stop().unwrap();
+
+        // No process of the group is left running, including the backgrounded sleep. Zombies are
+        // fine, they're only waiting to be reaped by init.
+        let processes = duct::cmd!("ps", "-eo", "pgid=,stat=").read().unwrap();
+        assert!(!processes.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            fields.next() == Some(&pid.to_string())
+                && !fields.next().unwrap_or("Z").starts_with('Z')
+        }));
+        let _ = std::fs::remove_file(log_path);
+    }
+
+    #[tokio::test]
+    async fn waiting_for_an_exited_process_gateway_should_fail_right_away() {
+        let mut gateway = GatewayInstance::spawn(
+            "test-exited",
+            "sh",
+            &[
+                "-c".to_string(),
+                "echo 'invalid config'; exit 3".to_string(),
+            ],
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        let start = std::time::Instant::now();
+        let err = wait_for_gateway_health_with_logs(&mut gateway)
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        insta::assert_snapshot!(err, @r#"
+        Gateway process exited with exit status: 3 before becoming healthy:
+        invalid config
+        "#);
+
+        let (_, args) = gateway.log_command();
+        let _ = std::fs::remove_file(args.last().unwrap());
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context as _, Result};
+use fast_glob::glob_match;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{BuildConfig, Config, Gateway, image_repository},
+    docker,
+};
+
+/// Files left out of the content hash of a build context without any `.dockerignore`, never
+/// worth hashing.
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &["**/.git", "**/target", "**/node_modules"];
+
+/// Build the images of all the gateways declaring a `build` section, once per image. Fails on
+/// the first build error so that a run doesn't start with a missing image.
+pub fn build_gateway_images<'a>(
+    config: &Config,
+    gateways: impl IntoIterator<Item = &'a Gateway>,
+) -> Result<()> {
+    let mut built = Vec::new();
+
+    for gateway in gateways {
+        let Some(build) = &gateway.config.build else {
+            continue;
+        };
+        if built.contains(&gateway.config.image) {
+            continue;
+        }
+
+        let tag = cached_image_tag(&config.current_dir, &gateway.config.image, build)?;
+        if docker::image_exists(&tag) {
+            tracing::info!(
+                "Image '{}' of gateway '{}' is up to date",
+                gateway.config.image,
+                gateway.name()
+            );
+        } else {
+            tracing::info!(
+                "Building image '{}' of gateway '{}' from {:?}",
+                gateway.config.image,
+                gateway.name(),
+                build.context
+            );
+            docker::build(&build_args(&config.current_dir, &tag, build))
+                .with_context(|| format!("Could not build gateway '{}'", gateway.name()))?;
+        }
+        docker::tag(&tag, &gateway.config.image)?;
+
+        built.push(gateway.config.image.clone());
+    }
+
+    Ok(())
+}
+
+/// Commands building the image of a gateway, or tagging the cached build if it's up to date.
+pub fn build_commands(current_dir: &Path, image: &str, build: &BuildConfig) -> Result<[String; 2]> {
+    let tag = cached_image_tag(current_dir, image, build)?;
+    Ok([
+        docker::format_command([], "docker", &build_args(current_dir, &tag, build)),
+        docker::format_command([], "docker", &["tag".to_string(), tag, image.to_string()]),
+    ])
+}
+
+/// Arguments of the `docker` command building the image with the given tag.
+fn build_args(current_dir: &Path, tag: &str, build: &BuildConfig) -> Vec<String> {
+    docker::build_args(
+        tag,
+        &current_dir.join(&build.context),
+        build.dockerfile.as_deref(),
+        &build.args,
+    )
+}
+
+/// Image tag identifying the content of a build, e.g. `apollo-router-no-cache:3f1a...`.
+fn cached_image_tag(current_dir: &Path, image: &str, build: &BuildConfig) -> Result<String> {
+    let hash = content_hash(&current_dir.join(&build.context), build)?;
+    Ok(format!("{}:{:016x}", image_repository(image), hash))
+}
+
+/// Hash of every file sent to Docker in the build context with its path, the Dockerfile and
+/// build arguments.
+fn content_hash(context: &Path, build: &BuildConfig) -> Result<u64> {
+    if !context.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Build context {:?} doesn't exist or isn't a directory",
+            context
+        ));
+    }
+
+    let ignore = IgnorePatterns::read(context)?;
+    let mut files = Vec::new();
+    collect_files(context, context, &ignore, &mut files)?;
+    files.sort();
+
+    let mut data = Vec::new();
+    for path in &files {
+        let relative_path = path.strip_prefix(context).unwrap_or(path);
+        let file =
+            std::fs::File::open(path).with_context(|| format!("Could not read {:?}", path))?;
+        data.extend_from_slice(relative_path.to_string_lossy().as_bytes());
+        data.extend_from_slice(&rapidhash::v3::rapidhash_v3_file(file)?.to_le_bytes());
+    }
+
+    if let Some(dockerfile) = &build.dockerfile {
+        data.extend_from_slice(dockerfile.to_string_lossy().as_bytes());
+    }
+    for (key, value) in &build.args {
+        data.extend_from_slice(format!("{key}={value}\n").as_bytes());
+    }
+
+    Ok(rapidhash::v3::rapidhash_v3(&data))
+}
+
+/// Patterns of the `.dockerignore` of a build context, the last matching one deciding whether a
+/// file is ignored and those starting with `!` being exceptions.
+struct IgnorePatterns {
+    patterns: Vec<(String, bool)>,
+}
+
+impl IgnorePatterns {
+    fn read(context: &Path) -> Result<Self> {
+        let path = context.join(".dockerignore");
+        let patterns = if path.is_file() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read {:?}", path))?;
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| match line.strip_prefix('!') {
+                    Some(pattern) => (normalize_pattern(pattern), true),
+                    None => (normalize_pattern(line), false),
+                })
+                .collect()
+        } else {
+            DEFAULT_IGNORE_PATTERNS
+                .iter()
+                .map(|pattern| (pattern.to_string(), false))
+                .collect()
+        };
+        Ok(Self { patterns })
+    }
+
+    /// Whether the path, relative to the context, is ignored itself or through a parent directory.
+    fn is_ignored(&self, path: &str) -> bool {
+        let mut ignored = false;
+        for (pattern, is_exception) in &self.patterns {
+            let matches = std::iter::once(path)
+                .chain(path.match_indices('/').map(|(idx, _)| &path[..idx]))
+                .any(|path| glob_match(pattern, path));
+            if matches {
+                ignored = !is_exception;
+            }
+        }
+        ignored
+    }
+
+    /// Exceptions may re-include files of an ignored directory, which must then be walked.
+    fn has_exceptions(&self) -> bool {
+        self.patterns.iter().any(|(_, is_exception)| *is_exception)
+    }
+}
+
+fn normalize_pattern(pattern: &str) -> String {
+    pattern
+        .trim()
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn collect_files(
+    context: &Path,
+    dir: &Path,
+    ignore: &IgnorePatterns,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Could not read {:?}", dir))? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let relative_path = path
+            .strip_prefix(context)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let ignored = ignore.is_ignored(&relative_path);
+
+        if file_type.is_dir() {
+            if !ignored || ignore.has_exceptions() {
+                collect_files(context, &path, ignore, files)?;
+            }
+        } else if file_type.is_file() && !ignored {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_hash_should_only_change_with_the_build_inputs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Dockerfile"), "FROM scratch").unwrap();
+        std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+
+        let mut build = BuildConfig {
+            context: dir.to_path_buf(),
+            dockerfile: None,
+            args: Default::default(),
+        };
+        let hash = content_hash(dir, &build).unwrap();
+        assert_eq!(hash, content_hash(dir, &build).unwrap());
+
+        // Ignored directories don't change the hash
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        std::fs::write(dir.join("target").join("output"), "binary").unwrap();
+        assert_eq!(hash, content_hash(dir, &build).unwrap());
+
+        std::fs::write(dir.join("src").join("main.rs"), "fn main() { todo!() }").unwrap();
+        let modified = content_hash(dir, &build).unwrap();
+        assert_ne!(hash, modified);
+
+        build
+            .args
+            .insert("PROFILE".to_string(), "release".to_string());
+        assert_ne!(modified, content_hash(dir, &build).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(content_hash(dir, &build).is_err());
+    }
+
+    #[test]
+    fn content_hash_should_leave_out_dockerignored_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        std::fs::write(
+            dir.join(".dockerignore"),
+            "# Build outputs\n/target/\ndocs\n!docs/schema.graphql\n*.log\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("Dockerfile"), "FROM scratch").unwrap();
+        std::fs::write(dir.join("debug.log"), "").unwrap();
+        std::fs::write(dir.join("target").join("output"), "").unwrap();
+        std::fs::write(dir.join("docs").join("README.md"), "").unwrap();
+        std::fs::write(dir.join("docs").join("schema.graphql"), "").unwrap();
+
+        let ignore = IgnorePatterns::read(dir).unwrap();
+        let mut files = Vec::new();
+        collect_files(dir, dir, &ignore, &mut files).unwrap();
+        files.sort();
+        let files = files
+            .iter()
+            .map(|path| {
+                path.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(files, @r#"
+        .dockerignore
+        Dockerfile
+        docs/schema.graphql
+        "#);
+    }
+}
@@ -1,57 +1,113 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use duct::cmd;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path, time::Duration};
 
-#[derive(Debug, Serialize)]
+/// Test duration used by the K6 scripts when none is specified.
+const DEFAULT_DURATION: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct K6Run {
     pub start: time::OffsetDateTime,
     pub end: time::OffsetDateTime,
     pub summary: K6Summary,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct K6Summary {
     pub state: K6SummaryState,
     pub subgraph_stats: SubgraphStats,
     pub metrics: K6SummaryMetrics,
+    /// Latencies of each operation of a workload mix, by name.
+    #[serde(default)]
+    pub operations: BTreeMap<String, TrendMetric>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct K6SummaryState {
     #[serde(rename = "testRunDurationMs")]
     pub test_run_duration_ms: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SubgraphStats {
     pub count: u64,
+    /// Subgraph responses served from the cache, or executed on a cache miss. Both stay at 0
+    /// when the cache is disabled.
+    #[serde(default)]
+    pub cache_hit: u64,
+    #[serde(default)]
+    pub cache_miss: u64,
+    /// Requests received by each subgraph, by name.
+    #[serde(default)]
+    pub subgraphs: BTreeMap<String, SubgraphRequestStats>,
+    /// Number of faults injected by the subgraphs, by kind.
+    #[serde(default)]
+    pub faults: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubgraphRequestStats {
+    pub requests: u64,
+    /// Requests for root fields, as opposed to `_entities` requests.
+    pub root_requests: u64,
+    pub entities_requests: u64,
+    /// Total number of representations sent to `_entities`.
+    pub representations: u64,
+    /// Total size of the request bodies.
+    pub bytes: u64,
+    /// Requests which waited for a slot of the subgraph's concurrency limit, and those rejected
+    /// because its queue was full.
+    #[serde(default)]
+    pub queued: u64,
+    #[serde(default)]
+    pub rejected: u64,
+}
+
+impl SubgraphRequestStats {
+    /// Average number of representations of an `_entities` request.
+    pub fn average_representations(&self) -> f64 {
+        if self.entities_requests > 0 {
+            self.representations as f64 / self.entities_requests as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Average request body size in bytes.
+    pub fn average_bytes(&self) -> f64 {
+        if self.requests > 0 {
+            self.bytes as f64 / self.requests as f64
+        } else {
+            0.0
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct K6SummaryMetrics {
     pub http_req_duration: Option<TrendMetric>,
     pub checks: Option<CheckMetric>,
     pub http_reqs: Option<CounterMetric>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CounterMetric {
     pub values: CounterValues,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CounterValues {
     pub count: f64,
     pub rate: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendMetric {
     pub values: TrendValues,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendValues {
     pub count: u64,
     pub avg: f64,
@@ -66,17 +122,30 @@ pub struct TrendValues {
     pub p99: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckMetric {
     pub values: HttpReqFailedValues,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpReqFailedValues {
     pub fails: u64,
 }
 
-pub async fn run(path: &Path, script: &str, duration: Option<&str>) -> Result<K6Run> {
+/// Reset the stats of the subgraphs served on `port`, so that they only count the requests of
+/// the load and not those of the gateway start-up or of the correctness check.
+pub async fn reset_subgraph_stats(port: u16) -> Result<()> {
+    reqwest::Client::new()
+        .post(format!("http://localhost:{port}/stats/reset"))
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Could not reset the subgraph stats on port {port}"))?;
+    Ok(())
+}
+
+/// Run the K6 script from the scenario directory `path`, in which the summary is written.
+pub async fn run(path: &Path, script: &Path, duration: Option<&str>) -> Result<K6Run> {
     let summary_path = path.join("summary.json");
 
     // Clean up any existing summary file
@@ -84,10 +153,7 @@ pub async fn run(path: &Path, script: &str, duration: Option<&str>) -> Result<K6
         std::fs::remove_file(&summary_path)?;
     }
 
-    tracing::info!(
-        "Starting K6 test with script {}",
-        path.join(script).display()
-    );
+    tracing::info!("Starting K6 test with script {}", script.display());
 
     let start = time::OffsetDateTime::now_utc();
 
@@ -126,3 +192,127 @@ pub async fn run(path: &Path, script: &str, duration: Option<&str>) -> Result<K6
         end,
     })
 }
+
+/// Duration of the K6 test, either the overridden one or the default of the script read from
+/// its `duration: __ENV.DURATION || "60s"` option.
+pub fn test_duration(script_path: &Path, duration: Option<&str>) -> Result<Duration> {
+    if let Some(duration) = duration {
+        return parse_duration(duration);
+    }
+
+    let script = std::fs::read_to_string(script_path)
+        .map_err(|e| anyhow::anyhow!("Could not read K6 script {:?}: {}", script_path, e))?;
+
+    let Some((_, rest)) = script.split_once("__ENV.DURATION ||") else {
+        return Ok(DEFAULT_DURATION);
+    };
+    let default = rest
+        .trim_start()
+        .trim_start_matches(['"', '\'', '`'])
+        .split(['"', '\'', '`'])
+        .next()
+        .unwrap_or_default();
+
+    parse_duration(default)
+}
+
+/// Parse a K6 duration such as "30s", "1m" or "2m30s".
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let mut total = Duration::ZERO;
+    let mut number = String::new();
+    let mut chars = value.trim().chars().peekable();
+
+    if chars.peek().is_none() {
+        return Err(anyhow::anyhow!("Empty duration"));
+    }
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let mut unit = c.to_string();
+        while let Some(&c) = chars.peek()
+            && c.is_ascii_alphabetic()
+        {
+            unit.push(c);
+            chars.next();
+        }
+
+        let amount: f64 = number
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid duration '{}'", value))?;
+        number.clear();
+
+        let unit_secs = match unit.as_str() {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Invalid duration unit '{}' in '{}'",
+                    unit,
+                    value
+                ));
+            }
+        };
+        total += Duration::from_secs_f64(amount * unit_secs);
+    }
+
+    if !number.is_empty() {
+        return Err(anyhow::anyhow!("Missing duration unit in '{}'", value));
+    }
+
+    Ok(total)
+}
+
+/// Format a duration in a human readable way, e.g. "1h 5m 30s".
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, _) => format!("{minutes}m {seconds}s"),
+        _ => format!("{hours}h {minutes}m {seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_k6_durations() {
+        let durations: Vec<String> = ["30s", "1m", "2m30s", "1h", "500ms", "", "30", "30x"]
+            .into_iter()
+            .map(|value| match parse_duration(value) {
+                Ok(duration) => format!("{value:?}: {duration:?}"),
+                Err(e) => format!("{value:?}: {e}"),
+            })
+            .collect();
+        insta::assert_snapshot!(durations.join("\n"), @r#"
+        "30s": 30s
+        "1m": 60s
+        "2m30s": 150s
+        "1h": 3600s
+        "500ms": 500ms
+        "": Empty duration
+        "30": Missing duration unit in '30'
+        "30x": Invalid duration unit 'x' in '30x'
+        "#);
+    }
+
+    #[test]
+    fn should_format_durations_with_their_largest_units() {
+        let formatted = [45, 150, 3930].map(|secs| format_duration(Duration::from_secs(secs)));
+        insta::assert_debug_snapshot!(formatted, @r#"
+        [
+            "45s",
+            "2m 30s",
+            "1h 5m 30s",
+        ]
+        "#);
+    }
+}
@@ -0,0 +1,1270 @@
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    correctness::Expected,
+    gateway::GATEWAY_PORT,
+    replay::{ANONYMOUS_OPERATION, ReplayConfig},
+    variables::{Generator, generated_variables},
+};
+
+/// Name of the K6 script generated from the `load` of a scenario, within its directory.
+pub const GENERATED_SCRIPT: &str = "k6.generated.js";
+
+/// Load of a scenario, from which the cli generates the K6 script:
+///
+/// ```toml
+/// [scenarios.query.load]
+/// query = "query.graphql"
+/// executor = { type = "constant-arrival-rate", rate = 500 }
+/// validation = "size"
+/// ```
+///
+/// Instead of a single request, a load can send a weighted mix of `operations` or `replay`
+/// recorded traffic.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoadConfig {
+    /// GraphQL request sent as is, relative to the scenario directory.
+    pub body: Option<PathBuf>,
+    /// GraphQL query sent with the `variables` and `operation_name`, relative to the scenario
+    /// directory.
+    pub query: Option<PathBuf>,
+    #[serde(default)]
+    pub variables: toml::Table,
+    /// Variables generated for each request, replacing the static ones.
+    #[serde(default)]
+    pub generators: BTreeMap<String, Generator>,
+    pub operation_name: Option<String>,
+    /// Operations picked randomly for each request according to their weight, instead of the
+    /// single `body` or `query`.
+    #[serde(default)]
+    pub operations: Vec<OperationConfig>,
+    /// Recorded requests replayed instead of the ones above.
+    pub replay: Option<ReplayConfig>,
+    /// How K6 sends the requests, implicit for a replay at the recorded timing.
+    pub executor: Option<Executor>,
+    /// Test duration, unless overridden with `--duration`.
+    #[serde(default = "default_duration")]
+    pub duration: String,
+    /// Headers added to every request, replacing the default ones. By default, every request has
+    /// a unique `authorization` header.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// How responses are checked, `size` by default. Replayed responses are only checked for
+    /// errors.
+    pub validation: Option<Validation>,
+    /// Expected response size for the `size` validation of responses too big to be committed.
+    pub expected_size: Option<usize>,
+}
+
+/// An operation of a workload mix. Its expected response is stored in the `<name>/`
+/// sub-directory of the scenario.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperationConfig {
+    /// Name tagging the K6 metrics of the operation.
+    pub name: String,
+    /// Share of the requests relative to the weights of the other operations.
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub body: Option<PathBuf>,
+    pub query: Option<PathBuf>,
+    #[serde(default)]
+    pub variables: toml::Table,
+    #[serde(default)]
+    pub generators: BTreeMap<String, Generator>,
+    pub operation_name: Option<String>,
+    pub expected_size: Option<usize>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_duration() -> String {
+    "60s".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Executor {
+    /// A fixed number of virtual users sending requests in a loop.
+    ConstantVus { vus: u32 },
+    /// A fixed request rate per second, whatever the response latencies.
+    ConstantArrivalRate {
+        rate: u32,
+        #[serde(default = "default_pre_allocated_vus")]
+        pre_allocated_vus: u32,
+        #[serde(default = "default_max_vus")]
+        max_vus: u32,
+    },
+}
+
+fn default_pre_allocated_vus() -> u32 {
+    10
+}
+
+fn default_max_vus() -> u32 {
+    200
+}
+
+/// How K6 checks each response.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Validation {
+    /// Only the status code.
+    Status,
+    /// Same size as `expected.json`, the response size of `expected-hash.json` or `expected_size`. Gateways don't all return the fields in
+    /// the same order.
+    #[default]
+    Size,
+    /// Same JSON as `expected.json`, regardless of the order of the object keys.
+    Exact,
+    /// GraphQL errors are accepted as long as they have a path leading to a null in the data,
+    /// which must not be null itself, for subgraphs injecting faults.
+    Errors,
+}
+
+/// A request sent by a scenario, with the directory holding its expected response.
+#[derive(Debug, Clone)]
+pub struct LoadRequest {
+    /// Name of the operation within a workload mix.
+    pub operation: Option<String>,
+    pub weight: u32,
+    /// Body of the GraphQL request sent to the gateway.
+    pub body: String,
+    /// Variables generated for each request, the responses are then only checked for errors.
+    pub generators: BTreeMap<String, Generator>,
+    pub expected_size: Option<usize>,
+    pub fixture_dir: PathBuf,
+}
+
+impl LoadRequest {
+    /// Label of the request in messages, e.g. `mixed/me` for an operation.
+    pub fn label(&self, scenario_name: &str) -> String {
+        match &self.operation {
+            Some(operation) => format!("{scenario_name}/{operation}"),
+            None => scenario_name.to_string(),
+        }
+    }
+
+    /// Whether every request sends the same body, which can be checked against an expected
+    /// response.
+    pub fn is_static(&self) -> bool {
+        self.generators.is_empty()
+    }
+}
+
+impl LoadConfig {
+    /// Requests sent by the scenario, a single one unless it defines `operations`.
+    pub fn requests(&self, scenario_path: &Path) -> Result<Vec<LoadRequest>> {
+        // Recorded requests have no expected response.
+        if self.replay.is_some() {
+            if self.body.is_some() || self.query.is_some() || !self.operations.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Scenario load cannot define both a `replay` and a `body`, `query` or `operations`"
+                ));
+            }
+            if self.validation.is_some() || self.expected_size.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Scenario load cannot define a `validation` or `expected_size` with a `replay`, its responses are only checked for errors"
+                ));
+            }
+            return Ok(Vec::new());
+        }
+
+        if self.operations.is_empty() {
+            let body = request_body(
+                scenario_path,
+                self.body.as_deref(),
+                self.query.as_deref(),
+                &self.variables,
+                self.operation_name.as_deref(),
+            )?;
+            return Ok(vec![LoadRequest {
+                operation: None,
+                weight: 1,
+                body,
+                generators: self.generators.clone(),
+                expected_size: self.expected_size,
+                fixture_dir: scenario_path.to_path_buf(),
+            }]);
+        }
+
+        if self.body.is_some() || self.query.is_some() {
+            return Err(anyhow::anyhow!(
+                "Scenario load cannot define both `operations` and a `body` or `query`"
+            ));
+        }
+        if self
+            .operations
+            .iter()
+            .all(|operation| operation.weight == 0)
+        {
+            return Err(anyhow::anyhow!(
+                "Scenario load operations must have a positive total weight"
+            ));
+        }
+
+        let mut requests: Vec<LoadRequest> = Vec::with_capacity(self.operations.len());
+        for operation in &self.operations {
+            if requests
+                .iter()
+                .any(|request| request.operation.as_ref() == Some(&operation.name))
+            {
+                return Err(anyhow::anyhow!(
+                    "Duplicate operation '{}' in scenario load",
+                    operation.name
+                ));
+            }
+            let body = request_body(
+                scenario_path,
+                operation.body.as_deref(),
+                operation.query.as_deref(),
+                &operation.variables,
+                operation.operation_name.as_deref(),
+            )
+            .with_context(|| format!("Invalid operation '{}'", operation.name))?;
+            requests.push(LoadRequest {
+                operation: Some(operation.name.clone()),
+                weight: operation.weight,
+                body,
+                generators: operation.generators.clone(),
+                expected_size: operation.expected_size,
+                fixture_dir: scenario_path.join(&operation.name),
+            });
+        }
+
+        Ok(requests)
+    }
+
+    /// Generate the K6 script of the scenario, reporting the subgraph stats of the service on
+    /// `stats_port`.
+    pub fn generate_script(&self, scenario_path: &Path, stats_port: u16) -> Result<String> {
+        let duration = &self.duration;
+        let executor = match (&self.replay, &self.executor) {
+            (Some(ReplayConfig { speed: Some(_), .. }), Some(_)) => {
+                return Err(anyhow::anyhow!(
+                    "Scenario load cannot define an `executor` with a replay at the recorded timing"
+                ));
+            }
+            (
+                Some(ReplayConfig {
+                    speed: Some(_),
+                    vus,
+                    ..
+                }),
+                None,
+            ) => format!(
+                "      executor: \"shared-iterations\",\n      vus: {vus},\n      iterations: requests.length,\n      maxDuration: __ENV.DURATION || \"{duration}\",\n"
+            ),
+            (_, Some(Executor::ConstantVus { vus })) => format!(
+                "      executor: \"constant-vus\",\n      vus: {vus},\n      duration: __ENV.DURATION || \"{duration}\",\n"
+            ),
+            (
+                _,
+                Some(Executor::ConstantArrivalRate {
+                    rate,
+                    pre_allocated_vus,
+                    max_vus,
+                }),
+            ) => format!(
+                "      executor: \"constant-arrival-rate\",\n      rate: {rate},\n      timeUnit: \"1s\",\n      preAllocatedVUs: {pre_allocated_vus},\n      maxVUs: {max_vus},\n      duration: __ENV.DURATION || \"{duration}\",\n"
+            ),
+            (_, None) => {
+                return Err(anyhow::anyhow!("Scenario load must define an `executor`"));
+            }
+        };
+
+        let (imports, init, operation_names) = match &self.replay {
+            Some(replay) => replay_requests(replay, scenario_path)?,
+            None => self.operations_requests(scenario_path)?,
+        };
+
+        // K6 only reports the metrics of a tag in its summary if it has a threshold.
+        let thresholds = if operation_names.is_empty() {
+            String::new()
+        } else {
+            let thresholds = operation_names
+                .iter()
+                .map(|name| {
+                    Ok(format!(
+                        "    {}: [\"max>=0\"],\n",
+                        serde_json::to_string(&format!("http_req_duration{{operation:{name}}}"))?
+                    ))
+                })
+                .collect::<Result<String>>()?;
+            format!("  thresholds: {{\n{thresholds}  }},\n")
+        };
+        let operation_names = serde_json::to_string(&operation_names)?;
+
+        let mut headers = BTreeMap::from([(
+            "Content-Type".to_string(),
+            "\"application/json\"".to_string(),
+        )]);
+        if !self
+            .headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("authorization"))
+        {
+            headers.insert(
+                "authorization".to_string(),
+                "`Bearer ${generateRandomToken()}`".to_string(),
+            );
+        }
+        for (name, value) in &self.headers {
+            headers.insert(name.clone(), serde_json::to_string(value)?);
+        }
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                Ok(format!(
+                    "      {}: {},\n",
+                    serde_json::to_string(name)?,
+                    value
+                ))
+            })
+            .collect::<Result<String>>()?;
+
+        Ok(format!(
+            r#"// Generated by the cli from the scenario load in config.toml, do not edit.
+import http from "k6/http";
+import {{ check, sleep }} from "k6";
+import exec from "k6/execution";
+{imports}import {{ textSummary }} from "https://jslib.k6.io/k6-summary/0.1.0/index.js";
+
+{init}
+export const options = {{
+  scenarios: {{
+    load: {{
+{executor}      gracefulStop: "3s",
+    }},
+  }},
+{thresholds}}};
+
+// Responses without any expected one, with generated variables or replayed, are only checked for
+// GraphQL errors.
+function hasNoErrors(body) {{
+  return !body.includes('"errors":');
+}}
+
+// Errors, such as those of subgraph faults, must have a path leading to a null in the data,
+// or to one of its ancestors. The data itself must not be null.
+function hasValidErrors(body) {{
+  let response;
+  try {{
+    response = JSON.parse(body);
+  }} catch (e) {{
+    return false;
+  }}
+  if (!response || typeof response !== "object" || !response.data || typeof response.data !== "object") {{
+    return false;
+  }}
+  return (response.errors || []).every((error) => {{
+    if (typeof error.message !== "string" || !Array.isArray(error.path)) {{
+      return false;
+    }}
+    let value = response.data;
+    for (const segment of error.path) {{
+      if (value === null) {{
+        return true;
+      }}
+      if (typeof value !== "object") {{
+        return false;
+      }}
+      value = value[segment];
+    }}
+    return value === null;
+  }});
+}}
+
+// Exact responses are compared as JSON, gateways don't all return the object keys in the same
+// order.
+function isSameJson(body, expected) {{
+  let response;
+  try {{
+    response = JSON.parse(body);
+  }} catch (e) {{
+    return false;
+  }}
+  return isEqual(response, expected);
+}}
+
+function isEqual(a, b) {{
+  if (a === b) {{
+    return true;
+  }}
+  if (!a || !b || typeof a !== "object" || typeof b !== "object" || Array.isArray(a) !== Array.isArray(b)) {{
+    return false;
+  }}
+  const keys = Object.keys(a);
+  return keys.length === Object.keys(b).length && keys.every((key) => key in b && isEqual(a[key], b[key]));
+}}
+
+function pick(values) {{
+  return values[Math.floor(Math.random() * values.length)];
+}}
+
+// Generate a random token for this request, this ensures gateways do not abuse the
+// repetitive nature of the benchmark too much.
+function generateRandomToken() {{
+  return (
+    Math.random().toString(36).substring(2) +
+    Math.random().toString(36).substring(2)
+  );
+}}
+
+export default function () {{
+  const operation = nextOperation();
+  const payload = operation.request
+    ? JSON.stringify({{
+        ...operation.request,
+        variables: {{ ...operation.request.variables, ...operation.variables() }},
+      }})
+    : operation.payload;
+  const params = {{
+    headers: {{
+{headers}      ...operation.headers,
+    }},
+    tags: {{ operation: operation.name }},
+  }};
+  const response = http.post("http://localhost:{GATEWAY_PORT}/graphql", payload, params);
+
+  check(response, {{
+    "response code was 200": (resp) => resp.status === 200,
+    "response is correct": (resp) => {{
+      if (operation.isCorrect(resp.body)) {{
+        return true;
+      }}
+
+      console.log("Incorrect response", `Operation: ${{operation.name}}`, `Size:`, resp.body.length);
+
+      if (resp.body.length < 1000) {{
+        console.log("Response:", resp.body);
+      }} else {{
+        const json = resp.json();
+        if (!json || typeof json !== "object" || Array.isArray(json) || json.errors) {{
+          console.log(
+            "graphql_errors",
+            `‼️ Got GraphQL errors, here's a sample:`,
+            resp.body,
+          );
+        }}
+      }}
+
+      return false;
+    }},
+  }});
+}}
+
+export function handleSummary(data) {{
+  const stats = http.get("http://localhost:{stats_port}/stats");
+  data["subgraph_stats"] = stats.json();
+  data["operations"] = {{}};
+  for (const name of {operation_names}) {{
+    const metric = data.metrics[`http_req_duration{{operation:${{name}}}}`];
+    if (metric) {{
+      data["operations"][name] = metric;
+    }}
+  }}
+  return {{
+    "summary.json": JSON.stringify(data),
+    stdout: textSummary(data, {{ indent: " ", enableColors: true }}),
+  }};
+}}
+"#,
+        ))
+    }
+
+    /// Script imports, definition of the operations with `nextOperation()` picking one for each
+    /// request, and names of the operations to report for a single request or a workload mix.
+    fn operations_requests(&self, scenario_path: &Path) -> Result<(String, String, Vec<String>)> {
+        let requests = self.requests(scenario_path)?;
+
+        let mut expected = String::new();
+        let mut operations = String::new();
+        for (idx, request) in requests.iter().enumerate() {
+            let expected_path = match &request.operation {
+                Some(name) => format!("./{name}/expected.json"),
+                None => "./expected.json".to_string(),
+            };
+            let validation = self.validation.unwrap_or_default();
+            // Responses too big to be committed are checked against the size stored with their hash.
+            let expected_size = match request.expected_size {
+                Some(size) => Some(size),
+                None => Expected::load(&request.fixture_dir)?.and_then(|e| e.response_size()),
+            };
+            let is_correct = match (validation, expected_size) {
+                (Validation::Errors, _) => "hasValidErrors(body)".to_string(),
+                _ if !request.is_static() => "hasNoErrors(body)".to_string(),
+                (Validation::Status, _) => "true".to_string(),
+                (Validation::Size, Some(size)) => format!("body.length === {size}"),
+                (Validation::Size, None) => {
+                    expected.push_str(&format!(
+                        "const expected{idx} = open({});\n",
+                        serde_json::to_string(&expected_path)?
+                    ));
+                    format!("body.length === expected{idx}.length")
+                }
+                (Validation::Exact, _) => {
+                    expected.push_str(&format!(
+                        "const expected{idx} = JSON.parse(open({}));\n",
+                        serde_json::to_string(&expected_path)?
+                    ));
+                    format!("isSameJson(body, expected{idx})")
+                }
+            };
+            let payload = if request.is_static() {
+                format!("    payload: {},\n", serde_json::to_string(&request.body)?)
+            } else {
+                let body: serde_json::Value = serde_json::from_str(&request.body)
+                    .context("Request body with generated variables must be JSON")?;
+                format!(
+                    "    request: {body},\n    variables: () => ({}),\n",
+                    generated_variables(&request.generators)?
+                )
+            };
+            operations.push_str(&format!(
+                "  {{\n    name: {},\n    weight: {},\n{payload}    isCorrect: (body) => {is_correct},\n  }},\n",
+                serde_json::to_string(request.operation.as_deref().unwrap_or("default"))?,
+                request.weight,
+            ));
+        }
+
+        let init = format!(
+            r#"{expected}const operations = [
+{operations}];
+const totalWeight = operations.reduce((total, operation) => total + operation.weight, 0);
+
+// Pick an operation randomly according to the weights.
+function nextOperation() {{
+  let value = Math.random() * totalWeight;
+  for (const operation of operations) {{
+    value -= operation.weight;
+    if (value < 0) {{
+      return operation;
+    }}
+  }}
+  return operations[operations.length - 1];
+}}
+"#
+        );
+
+        let operation_names = if requests.len() > 1 {
+            requests
+                .into_iter()
+                .filter_map(|request| request.operation)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok((String::new(), init, operation_names))
+    }
+
+    /// Write the generated K6 script into the scenario directory, returning its path.
+    pub fn write_script(&self, scenario_path: &Path, stats_port: u16) -> Result<PathBuf> {
+        std::fs::create_dir_all(scenario_path)
+            .with_context(|| format!("Could not create {:?}", scenario_path))?;
+        let path = scenario_path.join(GENERATED_SCRIPT);
+        std::fs::write(&path, self.generate_script(scenario_path, stats_port)?)
+            .with_context(|| format!("Could not write {:?}", path))?;
+        Ok(path)
+    }
+}
+
+/// Script imports, loading of the recorded requests with `nextOperation()` returning the next
+/// one to replay, and names of their operations.
+fn replay_requests(
+    replay: &ReplayConfig,
+    scenario_path: &Path,
+) -> Result<(String, String, Vec<String>)> {
+    let recording = replay.read(scenario_path)?;
+    let operation_names = recording
+        .iter()
+        .map(|request| request.operation().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let next_operation = match replay.speed {
+        Some(speed) => format!(
+            r#"// Wait for the recorded time of the next request, relative to the first one.
+function nextOperation() {{
+  const request = requests[exec.scenario.iterationInTest];
+  const delay =
+    exec.scenario.startTime + (request.timestamp - requests[0].timestamp) / {speed:?} - Date.now();
+  if (delay > 0) {{
+    sleep(delay / 1000);
+  }}
+  return {{ ...request, isCorrect: hasNoErrors }};
+}}
+"#
+        ),
+        None => r#"// Cycle through the recorded requests in order.
+function nextOperation() {
+  const request = requests[exec.scenario.iterationInTest % requests.length];
+  return { ...request, isCorrect: hasNoErrors };
+}
+"#
+        .to_string(),
+    };
+
+    let init = format!(
+        r#"const requests = new SharedArray("requests", () =>
+  open({file})
+    .split("\n")
+    .filter((line) => line.trim() !== "")
+    .map((line) => {{
+      const request = JSON.parse(line);
+      return {{
+        name: request.operationName || "{ANONYMOUS_OPERATION}",
+        payload: JSON.stringify({{
+          query: request.query,
+          variables: request.variables,
+          operationName: request.operationName,
+        }}),
+        headers: request.headers || {{}},
+        timestamp: request.timestamp || 0,
+      }};
+    }}),
+);
+
+{next_operation}"#,
+        file = serde_json::to_string(&format!("./{}", replay.file.display()))?,
+    );
+
+    let imports = "import { SharedArray } from \"k6/data\";\n".to_string();
+
+    Ok((imports, init, operation_names))
+}
+
+/// Body of a GraphQL request, either read as is or built from a query and its variables.
+fn request_body(
+    scenario_path: &Path,
+    body: Option<&Path>,
+    query: Option<&Path>,
+    variables: &toml::Table,
+    operation_name: Option<&str>,
+) -> Result<String> {
+    match (body, query) {
+        (Some(body), None) => read(scenario_path, body),
+        (None, Some(query)) => {
+            let mut request = serde_json::json!({
+                "query": read(scenario_path, query)?,
+            });
+            if !variables.is_empty() {
+                request["variables"] = serde_json::to_value(variables)?;
+            }
+            if let Some(operation_name) = operation_name {
+                request["operationName"] = operation_name.into();
+            }
+            Ok(request.to_string())
+        }
+        _ => Err(anyhow::anyhow!(
+            "Scenario load must define exactly one of `body` or `query`"
+        )),
+    }
+}
+
+/// File of a scenario, possibly shared with another one through a `../<scenario>/` path. `..` is
+/// resolved lexically, as a scenario without any file of its own has no directory.
+pub fn scenario_file(scenario_path: &Path, file: &Path) -> PathBuf {
+    let mut path = scenario_path.to_path_buf();
+    for component in file.components() {
+        match component {
+            Component::ParentDir if path.file_name().is_some() => {
+                path.pop();
+            }
+            component => path.push(component),
+        }
+    }
+    path
+}
+
+fn read(scenario_path: &Path, file: &Path) -> Result<String> {
+    let path = scenario_file(scenario_path, file);
+    std::fs::read_to_string(&path).with_context(|| format!("Could not read {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Part of the script generated from the load, before the helpers common to all scripts.
+    fn generated_part(script: &str) -> &str {
+        let (generated, _) = script
+            .split_once("// Responses without any expected one")
+            .unwrap();
+        generated
+    }
+
+    #[test]
+    fn should_generate_the_script_of_a_single_request() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("query.graphql"), "query Me { me { id } }").unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+query = "query.graphql"
+operation_name = "Me"
+variables = { first = 10 }
+executor = { type = "constant-arrival-rate", rate = 500 }
+headers = { authorization = "static" }
+"#,
+        )
+        .unwrap();
+
+        let requests = load.requests(dir).unwrap();
+        let bodies: Vec<&str> = requests.iter().map(|r| r.body.as_str()).collect();
+        insta::assert_debug_snapshot!(bodies, @r#"
+        [
+            "{\"operationName\":\"Me\",\"query\":\"query Me { me { id } }\",\"variables\":{\"first\":10}}",
+        ]
+        "#);
+
+        let script = load.generate_script(dir, 7200).unwrap();
+        insta::assert_snapshot!(script, @r#"
+        // Generated by the cli from the scenario load in config.toml, do not edit.
+        import http from "k6/http";
+        import { check, sleep } from "k6";
+        import exec from "k6/execution";
+        import { textSummary } from "https://jslib.k6.io/k6-summary/0.1.0/index.js";
+
+        const expected0 = open("./expected.json");
+        const operations = [
+          {
+            name: "default",
+            weight: 1,
+            payload: "{\"operationName\":\"Me\",\"query\":\"query Me { me { id } }\",\"variables\":{\"first\":10}}",
+            isCorrect: (body) => body.length === expected0.length,
+          },
+        ];
+        const totalWeight = operations.reduce((total, operation) => total + operation.weight, 0);
+
+        // Pick an operation randomly according to the weights.
+        function nextOperation() {
+          let value = Math.random() * totalWeight;
+          for (const operation of operations) {
+            value -= operation.weight;
+            if (value < 0) {
+              return operation;
+            }
+          }
+          return operations[operations.length - 1];
+        }
+
+        export const options = {
+          scenarios: {
+            load: {
+              executor: "constant-arrival-rate",
+              rate: 500,
+              timeUnit: "1s",
+              preAllocatedVUs: 10,
+              maxVUs: 200,
+              duration: __ENV.DURATION || "60s",
+              gracefulStop: "3s",
+            },
+          },
+        };
+
+        // Responses without any expected one, with generated variables or replayed, are only checked for
+        // GraphQL errors.
+        function hasNoErrors(body) {
+          return !body.includes('"errors":');
+        }
+
+        // Errors, such as those of subgraph faults, must have a path leading to a null in the data,
+        // or to one of its ancestors. The data itself must not be null.
+        function hasValidErrors(body) {
+          let response;
+          try {
+            response = JSON.parse(body);
+          } catch (e) {
+            return false;
+          }
+          if (!response || typeof response !== "object" || !response.data || typeof response.data !== "object") {
+            return false;
+          }
+          return (response.errors || []).every((error) => {
+            if (typeof error.message !== "string" || !Array.isArray(error.path)) {
+              return false;
+            }
+            let value = response.data;
+            for (const segment of error.path) {
+              if (value === null) {
+                return true;
+              }
+              if (typeof value !== "object") {
+                return false;
+              }
+              value = value[segment];
+            }
+            return value === null;
+          });
+        }
+
+        // Exact responses are compared as JSON, gateways don't all return the object keys in the same
+        // order.
+        function isSameJson(body, expected) {
+          let response;
+          try {
+            response = JSON.parse(body);
+          } catch (e) {
+            return false;
+          }
+          return isEqual(response, expected);
+        }
+
+        function isEqual(a, b) {
+          if (a === b) {
+            return true;
+          }
+          if (!a || !b || typeof a !== "object" || typeof b !== "object" || Array.isArray(a) !== Array.isArray(b)) {
+            return false;
+          }
+          const keys = Object.keys(a);
+          return keys.length === Object.keys(b).length && keys.every((key) => key in b && isEqual(a[key], b[key]));
+        }
+
+        function pick(values) {
+          return values[Math.floor(Math.random() * values.length)];
+        }
+
+        // Generate a random token for this request, this ensures gateways do not abuse the
+        // repetitive nature of the benchmark too much.
+        function generateRandomToken() {
+          return (
+            Math.random().toString(36).substring(2) +
+            Math.random().toString(36).substring(2)
+          );
+        }
+
+        export default function () {
+          const operation = nextOperation();
+          const payload = operation.request
+            ? JSON.stringify({
+                ...operation.request,
+                variables: { ...operation.request.variables, ...operation.variables() },
+              })
+            : operation.payload;
+          const params = {
+            headers: {
+              "Content-Type": "application/json",
+              "authorization": "static",
+              ...operation.headers,
+            },
+            tags: { operation: operation.name },
+          };
+          const response = http.post("http://localhost:4000/graphql", payload, params);
+
+          check(response, {
+            "response code was 200": (resp) => resp.status === 200,
+            "response is correct": (resp) => {
+              if (operation.isCorrect(resp.body)) {
+                return true;
+              }
+
+              console.log("Incorrect response", `Operation: ${operation.name}`, `Size:`, resp.body.length);
+
+              if (resp.body.length < 1000) {
+                console.log("Response:", resp.body);
+              } else {
+                const json = resp.json();
+                if (!json || typeof json !== "object" || Array.isArray(json) || json.errors) {
+                  console.log(
+                    "graphql_errors",
+                    `‼️ Got GraphQL errors, here's a sample:`,
+                    resp.body,
+                  );
+                }
+              }
+
+              return false;
+            },
+          });
+        }
+
+        export function handleSummary(data) {
+          const stats = http.get("http://localhost:7200/stats");
+          data["subgraph_stats"] = stats.json();
+          data["operations"] = {};
+          for (const name of []) {
+            const metric = data.metrics[`http_req_duration{operation:${name}}`];
+            if (metric) {
+              data["operations"][name] = metric;
+            }
+          }
+          return {
+            "summary.json": JSON.stringify(data),
+            stdout: textSummary(data, { indent: " ", enableColors: true }),
+          };
+        }
+        "#);
+    }
+
+    #[test]
+    fn should_generate_the_script_of_a_workload_mix() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("me.graphql"), "{ me { id } }").unwrap();
+        std::fs::write(dir.join("products.graphql"), "{ topProducts { upc } }").unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+executor = { type = "constant-vus", vus = 1 }
+operations = [
+    { name = "me", weight = 70, query = "me.graphql" },
+    { name = "products", weight = 30, query = "products.graphql", expected_size = 42 },
+]
+"#,
+        )
+        .unwrap();
+
+        let requests: Vec<String> = load
+            .requests(dir)
+            .unwrap()
+            .iter()
+            .map(|request| {
+                format!(
+                    "{}: {} in {}",
+                    request.label("mixed"),
+                    request.body,
+                    request.fixture_dir.strip_prefix(dir).unwrap().display()
+                )
+            })
+            .collect();
+        insta::assert_snapshot!(requests.join("\n"), @r#"
+        mixed/me: {"query":"{ me { id } }"} in me
+        mixed/products: {"query":"{ topProducts { upc } }"} in products
+        "#);
+
+        let script = load.generate_script(dir, 7200).unwrap();
+        insta::assert_snapshot!(generated_part(&script), @r#"
+        // Generated by the cli from the scenario load in config.toml, do not edit.
+        import http from "k6/http";
+        import { check, sleep } from "k6";
+        import exec from "k6/execution";
+        import { textSummary } from "https://jslib.k6.io/k6-summary/0.1.0/index.js";
+
+        const expected0 = open("./me/expected.json");
+        const operations = [
+          {
+            name: "me",
+            weight: 70,
+            payload: "{\"query\":\"{ me { id } }\"}",
+            isCorrect: (body) => body.length === expected0.length,
+          },
+          {
+            name: "products",
+            weight: 30,
+            payload: "{\"query\":\"{ topProducts { upc } }\"}",
+            isCorrect: (body) => body.length === 42,
+          },
+        ];
+        const totalWeight = operations.reduce((total, operation) => total + operation.weight, 0);
+
+        // Pick an operation randomly according to the weights.
+        function nextOperation() {
+          let value = Math.random() * totalWeight;
+          for (const operation of operations) {
+            value -= operation.weight;
+            if (value < 0) {
+              return operation;
+            }
+          }
+          return operations[operations.length - 1];
+        }
+
+        export const options = {
+          scenarios: {
+            load: {
+              executor: "constant-vus",
+              vus: 1,
+              duration: __ENV.DURATION || "60s",
+              gracefulStop: "3s",
+            },
+          },
+          thresholds: {
+            "http_req_duration{operation:me}": ["max>=0"],
+            "http_req_duration{operation:products}": ["max>=0"],
+          },
+        };
+        "#);
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+executor = { type = "constant-vus", vus = 1 }
+operations = [{ name = "me", query = "me.graphql" }, { name = "me", query = "me.graphql" }]
+"#,
+        )
+        .unwrap();
+        let err = load.requests(dir).unwrap_err();
+        insta::assert_snapshot!(err, @"Duplicate operation 'me' in scenario load");
+    }
+
+    #[test]
+    fn should_generate_the_script_of_a_replay() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("requests.jsonl"),
+            "{\"query\":\"{ me { id } }\",\"operationName\":\"Me\",\"timestamp\":0}\n{\"query\":\"{ a }\",\"timestamp\":5}\n",
+        )
+        .unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+replay = { file = "requests.jsonl", speed = 2.0, vus = 20 }
+duration = "30s"
+"#,
+        )
+        .unwrap();
+        assert!(load.requests(dir).unwrap().is_empty());
+
+        let script = load.generate_script(dir, 7200).unwrap();
+        insta::assert_snapshot!(generated_part(&script), @r#"
+        // Generated by the cli from the scenario load in config.toml, do not edit.
+        import http from "k6/http";
+        import { check, sleep } from "k6";
+        import exec from "k6/execution";
+        import { SharedArray } from "k6/data";
+        import { textSummary } from "https://jslib.k6.io/k6-summary/0.1.0/index.js";
+
+        const requests = new SharedArray("requests", () =>
+          open("./requests.jsonl")
+            .split("\n")
+            .filter((line) => line.trim() !== "")
+            .map((line) => {
+              const request = JSON.parse(line);
+              return {
+                name: request.operationName || "anonymous",
+                payload: JSON.stringify({
+                  query: request.query,
+                  variables: request.variables,
+                  operationName: request.operationName,
+                }),
+                headers: request.headers || {},
+                timestamp: request.timestamp || 0,
+              };
+            }),
+        );
+
+        // Wait for the recorded time of the next request, relative to the first one.
+        function nextOperation() {
+          const request = requests[exec.scenario.iterationInTest];
+          const delay =
+            exec.scenario.startTime + (request.timestamp - requests[0].timestamp) / 2.0 - Date.now();
+          if (delay > 0) {
+            sleep(delay / 1000);
+          }
+          return { ...request, isCorrect: hasNoErrors };
+        }
+
+        export const options = {
+          scenarios: {
+            load: {
+              executor: "shared-iterations",
+              vus: 20,
+              iterations: requests.length,
+              maxDuration: __ENV.DURATION || "30s",
+              gracefulStop: "3s",
+            },
+          },
+          thresholds: {
+            "http_req_duration{operation:Me}": ["max>=0"],
+            "http_req_duration{operation:anonymous}": ["max>=0"],
+          },
+        };
+        "#);
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+replay = { file = "requests.jsonl", speed = 2.0 }
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+        let err = load.generate_script(dir, 7200).unwrap_err();
+        insta::assert_snapshot!(err, @"Scenario load cannot define an `executor` with a replay at the recorded timing");
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+replay = { file = "requests.jsonl" }
+validation = "exact"
+"#,
+        )
+        .unwrap();
+        let err = load.requests(dir).unwrap_err();
+        insta::assert_snapshot!(err, @"Scenario load cannot define a `validation` or `expected_size` with a `replay`, its responses are only checked for errors");
+    }
+
+    #[test]
+    fn generated_variables_should_only_be_checked_for_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("user.graphql"),
+            "query User($id: ID!) { user(id: $id) { id } }",
+        )
+        .unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+query = "user.graphql"
+generators.id = { type = "sequence", start = 1, end = 6 }
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+
+        let requests = load.requests(dir).unwrap();
+        assert!(!requests[0].is_static());
+
+        let script = load.generate_script(dir, 7200).unwrap();
+        insta::assert_snapshot!(generated_part(&script), @r#"
+        // Generated by the cli from the scenario load in config.toml, do not edit.
+        import http from "k6/http";
+        import { check, sleep } from "k6";
+        import exec from "k6/execution";
+        import { textSummary } from "https://jslib.k6.io/k6-summary/0.1.0/index.js";
+
+        const operations = [
+          {
+            name: "default",
+            weight: 1,
+            request: {"query":"query User($id: ID!) { user(id: $id) { id } }"},
+            variables: () => ({ "id": 1 + (exec.scenario.iterationInTest % 6) }),
+            isCorrect: (body) => hasNoErrors(body),
+          },
+        ];
+        const totalWeight = operations.reduce((total, operation) => total + operation.weight, 0);
+
+        // Pick an operation randomly according to the weights.
+        function nextOperation() {
+          let value = Math.random() * totalWeight;
+          for (const operation of operations) {
+            value -= operation.weight;
+            if (value < 0) {
+              return operation;
+            }
+          }
+          return operations[operations.length - 1];
+        }
+
+        export const options = {
+          scenarios: {
+            load: {
+              executor: "constant-vus",
+              vus: 1,
+              duration: __ENV.DURATION || "60s",
+              gracefulStop: "3s",
+            },
+          },
+        };
+        "#);
+
+        // Faults are expected to produce errors, with valid paths
+        let load: LoadConfig = toml::from_str(
+            r#"
+query = "user.graphql"
+validation = "errors"
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+        let script = load.generate_script(dir, 7200).unwrap();
+        let is_correct = script
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("isCorrect"))
+            .unwrap();
+        insta::assert_snapshot!(is_correct, @"isCorrect: (body) => hasValidErrors(body),");
+    }
+
+    #[test]
+    fn exact_validation_should_compare_the_parsed_json() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("body.json"), r#"{"query":"{ me { id } }"}"#).unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+body = "body.json"
+validation = "exact"
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+        let script = load.generate_script(dir, 7200).unwrap();
+        let checks = script
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("const expected") || line.starts_with("isCorrect"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(checks, @r#"
+        const expected0 = JSON.parse(open("./expected.json"));
+        isCorrect: (body) => isSameJson(body, expected0),
+        "#);
+    }
+
+    #[test]
+    fn hashed_fixtures_should_be_checked_by_their_response_size() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("body.json"), r#"{"query":"{ me { id } }"}"#).unwrap();
+        std::fs::write(
+            dir.join("expected-hash.json"),
+            r#"{"hash":"2b7ca1749bf80aae","size":7889164,"response_size":7893331}"#,
+        )
+        .unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+body = "body.json"
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+        let script = load.generate_script(dir, 7200).unwrap();
+        let is_correct = script
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("isCorrect"))
+            .unwrap();
+        insta::assert_snapshot!(is_correct, @"isCorrect: (body) => body.length === 7893331,");
+    }
+
+    #[test]
+    fn scenarios_can_share_the_files_of_another_one_without_a_directory_of_their_own() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("query")).unwrap();
+        std::fs::write(
+            dir.join("query").join("body.json"),
+            r#"{"query":"{ me { id } }"}"#,
+        )
+        .unwrap();
+
+        let load: LoadConfig = toml::from_str(
+            r#"
+body = "../query/body.json"
+executor = { type = "constant-vus", vus = 1 }
+"#,
+        )
+        .unwrap();
+        let scenario_path = dir.join("partial-failures");
+        let requests = load.requests(&scenario_path).unwrap();
+        insta::assert_snapshot!(requests[0].body, @r#"{"query":"{ me { id } }"}"#);
+
+        let script = load.write_script(&scenario_path, 7200).unwrap();
+        assert!(script.exists());
+    }
+}
@@ -1,13 +1,22 @@
 mod benchmark;
 mod charts;
 mod commands;
+mod composition;
 mod config;
+mod correctness;
 mod docker;
 mod gateway;
+mod images;
 mod k6;
+mod load;
+mod replay;
 mod report;
 mod resources;
+mod results;
+mod selector;
+mod supergraph;
 mod system;
+mod variables;
 
 use anyhow::Result;
 use commands::{CliArgs, Command};
@@ -26,18 +35,33 @@ async fn main() -> Result<()> {
 
     let args: CliArgs = argh::from_env();
     let current_dir = std::env::current_dir()?;
-    let ctx = Context::new(current_dir)?;
+    let ctx = Context::new(current_dir, &args.set)?;
 
     match args.command {
         Command::Bench(args) => {
             commands::bench::main(ctx, args).await?;
         }
+        Command::CheckSchemas(args) => {
+            commands::check_schemas::main(ctx, args).await?;
+        }
+        Command::Compose(args) => {
+            commands::compose::main(ctx, args).await?;
+        }
+        Command::Fixtures(args) => {
+            commands::fixtures::main(ctx, args).await?;
+        }
         Command::List(args) => {
             commands::list::main(ctx, args).await?;
         }
+        Command::Plans(args) => {
+            commands::plans::main(ctx, args).await?;
+        }
         Command::Run(args) => {
             commands::run::main(ctx, args).await?;
         }
+        Command::Validate(args) => {
+            commands::validate::main(ctx, args).await?;
+        }
     }
 
     Ok(())
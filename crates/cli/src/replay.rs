@@ -0,0 +1,158 @@
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Operation name used for the metrics of recorded requests without any.
+pub const ANONYMOUS_OPERATION: &str = "anonymous";
+
+/// Replay of recorded traffic, instead of the requests of the load:
+///
+/// ```toml
+/// [scenarios.replay.load]
+/// replay = { file = "requests.jsonl", speed = 2.0 }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplayConfig {
+    /// JSONL file of recorded requests, relative to the scenario directory.
+    pub file: PathBuf,
+    /// Replay the requests at their recorded `timestamp`, sped up by this factor, instead of
+    /// sending them at the rate of the executor.
+    pub speed: Option<f64>,
+    /// Virtual users sending the requests at their recorded timing, bounding the number of
+    /// concurrent requests.
+    #[serde(default = "default_vus")]
+    pub vus: u32,
+}
+
+fn default_vus() -> u32 {
+    100
+}
+
+/// A recorded GraphQL request, one JSON object per line of the replay file. K6 reads the file
+/// itself and sends its `variables` and `headers`, added to the default ones of the load, as
+/// recorded. The cli only validates the rest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedRequest {
+    pub query: String,
+    pub operation_name: Option<String>,
+    /// Milliseconds since the start of the recording.
+    pub timestamp: Option<f64>,
+}
+
+impl RecordedRequest {
+    /// Operation name tagging the metrics of the request.
+    pub fn operation(&self) -> &str {
+        self.operation_name
+            .as_deref()
+            .unwrap_or(ANONYMOUS_OPERATION)
+    }
+}
+
+impl ReplayConfig {
+    /// Read the recorded requests, ensuring they can be replayed at their recorded timing if
+    /// required.
+    pub fn read(&self, scenario_path: &Path) -> Result<Vec<RecordedRequest>> {
+        if let Some(speed) = self.speed
+            && speed <= 0.0
+        {
+            return Err(anyhow::anyhow!(
+                "Replay speed must be positive, got {}",
+                speed
+            ));
+        }
+
+        let path = scenario_path.join(&self.file);
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Could not read {:?}", path))?;
+
+        let mut requests: Vec<RecordedRequest> = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request: RecordedRequest = serde_json::from_str(line)
+                .with_context(|| format!("Invalid request at {:?}:{}", path, idx + 1))?;
+            async_graphql_parser::parse_query(&request.query)
+                .with_context(|| format!("Invalid query at {:?}:{}", path, idx + 1))?;
+
+            if self.speed.is_some() {
+                let Some(timestamp) = request.timestamp else {
+                    return Err(anyhow::anyhow!(
+                        "Request at {:?}:{} has no timestamp to be replayed at its recorded timing",
+                        path,
+                        idx + 1
+                    ));
+                };
+                if let Some(previous) = requests.last().and_then(|r| r.timestamp)
+                    && timestamp < previous
+                {
+                    return Err(anyhow::anyhow!(
+                        "Request at {:?}:{} is recorded before the previous one",
+                        path,
+                        idx + 1
+                    ));
+                }
+            }
+            requests.push(request);
+        }
+
+        if requests.is_empty() {
+            return Err(anyhow::anyhow!("No request recorded in {:?}", path));
+        }
+        Ok(requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_a_recording_but_must_reject_invalid_unordered_or_untimed_requests() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("requests.jsonl"),
+            r#"{"query":"query Me { me { id } }","operationName":"Me","timestamp":0}
+
+{"query":"{ topProducts { upc } }","variables":{"first":5},"headers":{"x-client":"web"},"timestamp":12.5}
+"#,
+        )
+        .unwrap();
+
+        let replay: ReplayConfig = toml::from_str(r#"file = "requests.jsonl""#).unwrap();
+        let requests = replay.read(dir).unwrap();
+        let operations: Vec<&str> = requests.iter().map(RecordedRequest::operation).collect();
+        insta::assert_debug_snapshot!(operations, @r#"
+        [
+            "Me",
+            "anonymous",
+        ]
+        "#);
+
+        std::fs::write(dir.join("invalid.jsonl"), "{\"query\":\"{ a \"}\n").unwrap();
+        let replay: ReplayConfig = toml::from_str("file = \"invalid.jsonl\"").unwrap();
+        let error = replay.read(dir).unwrap_err().to_string();
+        let error = error.replace(dir.to_str().unwrap(), "<dir>");
+        insta::assert_snapshot!(error, @r#"Invalid query at "<dir>/invalid.jsonl":1"#);
+
+        std::fs::write(
+            dir.join("unordered.jsonl"),
+            "{\"query\":\"{ a }\",\"timestamp\":10}\n{\"query\":\"{ b }\",\"timestamp\":5}\n",
+        )
+        .unwrap();
+        let replay: ReplayConfig =
+            toml::from_str("file = \"unordered.jsonl\"\nspeed = 1.0").unwrap();
+        let error = replay.read(dir).unwrap_err().to_string();
+        let error = error.replace(dir.to_str().unwrap(), "<dir>");
+        insta::assert_snapshot!(error, @r#"Request at "<dir>/unordered.jsonl":2 is recorded before the previous one"#);
+
+        std::fs::write(dir.join("untimed.jsonl"), "{\"query\":\"{ a }\"}\n").unwrap();
+        let replay: ReplayConfig = toml::from_str("file = \"untimed.jsonl\"\nspeed = 1.0").unwrap();
+        let error = replay.read(dir).unwrap_err().to_string();
+        let error = error.replace(dir.to_str().unwrap(), "<dir>");
+        insta::assert_snapshot!(error, @r#"Request at "<dir>/untimed.jsonl":1 has no timestamp to be replayed at its recorded timing"#);
+    }
+}
@@ -66,6 +66,17 @@ pub fn generate_report_with_options(
         if let Some(docker_version) = &system_info.docker_version {
             report.push_str(&format!("- Docker Version: {}\n", docker_version));
         }
+        if !config.overrides.is_empty() {
+            report.push_str(&format!(
+                "- Config overrides: {}\n",
+                config
+                    .overrides
+                    .iter()
+                    .map(|item| format!("`{item}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
 
         report.push_str("\n# Gateways\n\n");
 
@@ -75,12 +86,22 @@ pub fn generate_report_with_options(
         for result in results {
             gateway_images
                 .entry(&result.gateway.name)
-                .or_insert_with(|| &result.gateway.config.image);
+                .or_insert_with(|| &result.gateway.config);
         }
 
         report.push_str("The following gateways were tested (as configured in `config.toml`):\n\n");
-        for (name, image) in gateway_images {
-            report.push_str(&format!("- {name}: {image}\n"));
+        for (name, gateway) in gateway_images {
+            match (&gateway.command, &gateway.build) {
+                (Some(command), _) => {
+                    report.push_str(&format!("- {name}: `{command}` (host process)\n"))
+                }
+                (None, Some(build)) => report.push_str(&format!(
+                    "- {name}: {} (built from `{}`)\n",
+                    gateway.image,
+                    build.context.display()
+                )),
+                (None, None) => report.push_str(&format!("- {name}: {}\n", gateway.image)),
+            }
         }
         report.push('\n');
     }
@@ -194,6 +215,111 @@ pub fn generate_report_with_options(
             }
         }
 
+        // Latencies of each operation of a workload mix
+        let operations = benchmark_results
+            .iter()
+            .flat_map(|r| r.k6_run.summary.operations.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        if !operations.is_empty() {
+            if !options.is_tty {
+                report.push_str("\n## Operation latencies (ms)\n\n");
+                report.push_str(&format!(
+                    "![Operations Chart](charts/{}-operations.svg)\n\n",
+                    scenario_name
+                ));
+            } else {
+                report.push('\n');
+            }
+
+            let operation_width = operations
+                .iter()
+                .map(|name| name.len())
+                .max()
+                .unwrap_or(9)
+                .max(9); // At least as wide as "Operation"
+
+            report.push_str(&format!(
+                "| {:<op_width$} | {:<width$} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} |\n",
+                "Operation",
+                "Gateway",
+                "Min",
+                "Med",
+                "P90",
+                "P95",
+                "P99",
+                "Max",
+                op_width = operation_width,
+                width = gateway_width
+            ));
+            report.push_str(&format!(
+                "| {:-<op_width$} | {:-<width$} | {:->7} | {:->7} | {:->7} | {:->7} | {:->7} | {:->7} |\n",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                op_width = operation_width,
+                width = gateway_width
+            ));
+
+            for operation in operations {
+                // Same order as the latencies table, by median of the operation
+                let mut rows = benchmark_results
+                    .iter()
+                    .map(|r| {
+                        let values = r
+                            .k6_run
+                            .summary
+                            .operations
+                            .get(operation)
+                            .filter(|_| !r.has_failures())
+                            .map(|metric| &metric.values);
+                        (r, values)
+                    })
+                    .collect::<Vec<_>>();
+                rows.sort_by(|(a, a_values), (b, b_values)| match (a_values, b_values) {
+                    (Some(a), Some(b)) => a.med.total_cmp(&b.med),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => a.gateway.label().cmp(b.gateway.label()),
+                });
+
+                for (result, values) in rows {
+                    match values {
+                        Some(values) => report.push_str(&format!(
+                            "| {:<op_width$} | {:<width$} | {:>7.1} | {:>7.1} | {:>7.1} | {:>7.1} | {:>7.1} | {:>7.1} |\n",
+                            operation,
+                            result.gateway.label(),
+                            values.min,
+                            values.med,
+                            values.p90,
+                            values.p95,
+                            values.p99,
+                            values.max,
+                            op_width = operation_width,
+                            width = gateway_width
+                        )),
+                        None => report.push_str(&format!(
+                            "| {:<op_width$} | {:<width$} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} |\n",
+                            operation,
+                            result.gateway.label(),
+                            ERR_PLACEHOLDER,
+                            ERR_PLACEHOLDER,
+                            ERR_PLACEHOLDER,
+                            ERR_PLACEHOLDER,
+                            ERR_PLACEHOLDER,
+                            ERR_PLACEHOLDER,
+                            op_width = operation_width,
+                            width = gateway_width
+                        )),
+                    }
+                }
+            }
+        }
+
         if !options.is_tty {
             report.push_str("\n## Resources\n\n");
             // Add efficiency chart image before the table
@@ -303,16 +429,18 @@ pub fn generate_report_with_options(
         }
 
         report.push_str(&format!(
-            "| {:<width$} | {:>8} | {:>8} | {:>25} |\n",
+            "| {:<width$} | {:>8} | {:>8} | {:>25} | {:>19} |\n",
             "Gateway",
             "Requests",
             "Failures",
             "Subgraph requests (total)",
+            "Subgraph cache hits",
             width = gateway_width
         ));
 
         report.push_str(&format!(
-            "| {:-<width$} | {:->8} | {:->8} | {:->25} |\n",
+            "| {:-<width$} | {:->8} | {:->8} | {:->25} | {:->19} |\n",
+            ":",
             ":",
             ":",
             ":",
@@ -355,15 +483,261 @@ pub fn generate_report_with_options(
             } else {
                 "0 (0)".to_string()
             };
+            // Cache hits are meaningless when the subgraphs cache is disabled
+            let cache_hits = match result.subgraph_cache_hit_rate() {
+                Some(rate) => format!("{:.1}%", rate * 100.0),
+                None => "-".to_string(),
+            };
 
             report.push_str(&format!(
-                "| {:<width$} | {:>8} | {:>8} | {:>25} |\n",
+                "| {:<width$} | {:>8} | {:>8} | {:>25} | {:>19} |\n",
                 result.gateway.label(),
                 requests_count,
                 failures,
                 sub,
+                cache_hits,
+                width = gateway_width
+            ));
+        }
+
+        // Requests received by each subgraph
+        if sorted_results
+            .iter()
+            .any(|r| !r.k6_run.summary.subgraph_stats.subgraphs.is_empty())
+        {
+            if !options.is_tty {
+                report.push_str("\n### Subgraph requests\n\n");
+            } else {
+                report.push('\n');
+            }
+
+            let subgraph_width = sorted_results
+                .iter()
+                .flat_map(|r| r.k6_run.summary.subgraph_stats.subgraphs.keys())
+                .map(|name| name.len())
+                .max()
+                .unwrap_or(0)
+                .max("Subgraph".len());
+
+            report.push_str(&format!(
+                "| {:<width$} | {:<subgraph_width$} | {:>18} | {:>8} | {:>9} | {:>15} | {:>14} |\n",
+                "Gateway",
+                "Subgraph",
+                "Requests (total)",
+                "Root",
+                "_entities",
+                "Representations",
+                "Avg size (B)",
                 width = gateway_width
             ));
+            report.push_str(&format!(
+                "| {:-<width$} | {:-<subgraph_width$} | {:->18} | {:->8} | {:->9} | {:->15} | {:->14} |\n",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                ":",
+                width = gateway_width
+            ));
+
+            for result in sorted_results.iter() {
+                let requests_count = result.request_count();
+                for (name, stats) in &result.k6_run.summary.subgraph_stats.subgraphs {
+                    let per_request = if requests_count > 0 {
+                        stats.requests as f64 / requests_count as f64
+                    } else {
+                        0.0
+                    };
+                    report.push_str(&format!(
+                        "| {:<width$} | {:<subgraph_width$} | {:>18} | {:>8} | {:>9} | {:>15.1} | {:>14.0} |\n",
+                        result.gateway.label(),
+                        name,
+                        format!("{:.2} ({})", per_request, stats.requests),
+                        stats.root_requests,
+                        stats.entities_requests,
+                        stats.average_representations(),
+                        stats.average_bytes(),
+                        width = gateway_width
+                    ));
+                }
+            }
+        }
+
+        // Faults injected by the subgraphs during the load
+        if sorted_results
+            .iter()
+            .any(|r| !r.k6_run.summary.subgraph_stats.faults.is_empty())
+        {
+            if !options.is_tty {
+                report.push_str("\n### Injected subgraph faults\n\n");
+            } else {
+                report.push('\n');
+            }
+
+            report.push_str(&format!(
+                "| {:<width$} | {:<16} | {:>8} | {:>26} |\n",
+                "Gateway",
+                "Fault",
+                "Count",
+                "Share of subgraph requests",
+                width = gateway_width
+            ));
+            report.push_str(&format!(
+                "| {:-<width$} | {:-<16} | {:->8} | {:->26} |\n",
+                ":",
+                ":",
+                ":",
+                ":",
+                width = gateway_width
+            ));
+
+            for result in sorted_results.iter() {
+                let stats = &result.k6_run.summary.subgraph_stats;
+                for (fault, count) in &stats.faults {
+                    let share = if stats.count > 0 {
+                        format!("{:.1}%", *count as f64 / stats.count as f64 * 100.0)
+                    } else {
+                        "-".to_string()
+                    };
+                    report.push_str(&format!(
+                        "| {:<width$} | {:<16} | {:>8} | {:>26} |\n",
+                        result.gateway.label(),
+                        fault,
+                        count,
+                        share,
+                        width = gateway_width
+                    ));
+                }
+            }
+        }
+
+        // Requests beyond the concurrency limit of the subgraphs
+        if sorted_results.iter().any(|r| {
+            r.k6_run
+                .summary
+                .subgraph_stats
+                .subgraphs
+                .values()
+                .any(|stats| stats.queued > 0 || stats.rejected > 0)
+        }) {
+            if !options.is_tty {
+                report.push_str("\n### Subgraph capacity\n\n");
+            } else {
+                report.push('\n');
+            }
+
+            let subgraph_width = sorted_results
+                .iter()
+                .flat_map(|r| r.k6_run.summary.subgraph_stats.subgraphs.keys())
+                .map(|name| name.len())
+                .max()
+                .unwrap_or(0)
+                .max("Subgraph".len());
+
+            report.push_str(&format!(
+                "| {:<width$} | {:<subgraph_width$} | {:>16} | {:>16} |\n",
+                "Gateway",
+                "Subgraph",
+                "Queued",
+                "Rejected",
+                width = gateway_width
+            ));
+            report.push_str(&format!(
+                "| {:-<width$} | {:-<subgraph_width$} | {:->16} | {:->16} |\n",
+                ":",
+                ":",
+                ":",
+                ":",
+                width = gateway_width
+            ));
+
+            for result in sorted_results.iter() {
+                for (name, stats) in &result.k6_run.summary.subgraph_stats.subgraphs {
+                    let with_share = |count: u64| {
+                        if stats.requests > 0 {
+                            format!(
+                                "{} ({:.1}%)",
+                                count,
+                                count as f64 / stats.requests as f64 * 100.0
+                            )
+                        } else {
+                            count.to_string()
+                        }
+                    };
+                    report.push_str(&format!(
+                        "| {:<width$} | {:<subgraph_width$} | {:>16} | {:>16} |\n",
+                        result.gateway.label(),
+                        name,
+                        with_share(stats.queued),
+                        with_share(stats.rejected),
+                        width = gateway_width
+                    ));
+                }
+            }
+        }
+
+        // Response check done before the load
+        if benchmark_results.iter().any(|r| r.correctness.is_some()) {
+            if !options.is_tty {
+                report.push_str("\n## Correctness\n\n");
+            } else {
+                report.push('\n');
+            }
+
+            report.push_str(&format!(
+                "| {:<width$} | {:<16} |\n",
+                "Gateway",
+                "Response",
+                width = gateway_width
+            ));
+            report.push_str(&format!(
+                "| {:-<width$} | {:-<16} |\n",
+                ":",
+                ":",
+                width = gateway_width
+            ));
+
+            let mut sorted_results = benchmark_results.clone();
+            sorted_results.sort_by(|a, b| a.gateway.label().cmp(b.gateway.label()));
+
+            for result in &sorted_results {
+                let verdict = match &result.correctness {
+                    Some(correctness) if correctness.is_correct() => "correct".to_string(),
+                    Some(correctness) => format!("{} difference(s)", correctness.differences),
+                    None => "not checked".to_string(),
+                };
+                report.push_str(&format!(
+                    "| {:<width$} | {:<16} |\n",
+                    result.gateway.label(),
+                    verdict,
+                    width = gateway_width
+                ));
+            }
+
+            for result in &sorted_results {
+                if let Some(correctness) = &result.correctness
+                    && !correctness.is_correct()
+                {
+                    report.push_str(&format!(
+                        "\n{}:\n\n```\n{}\n```\n",
+                        result.gateway.label(),
+                        correctness.excerpt.join("\n")
+                    ));
+                }
+            }
+        }
+
+        // Version trends of gateways benchmarked with multiple versions
+        if !options.is_tty {
+            for (gateway, _) in crate::charts::group_by_version(&benchmark_results, config)? {
+                report.push_str(&format!("\n## {} versions\n\n", gateway.label()));
+                report.push_str(&format!(
+                    "![Versions Chart](charts/{})\n",
+                    crate::charts::versions_chart_filename(&scenario_name, &gateway)
+                ));
+            }
         }
 
         report.push('\n');
@@ -377,9 +751,11 @@ mod tests {
     use super::*;
     use crate::benchmark::BenchmarkResult;
     use crate::config::{Config, Gateway, ScenarioConfig};
+    use crate::correctness::Correctness;
     use crate::k6::{
         CheckMetric, CounterMetric, CounterValues, HttpReqFailedValues, K6Run, K6Summary,
-        K6SummaryMetrics, K6SummaryState, SubgraphStats, TrendMetric, TrendValues,
+        K6SummaryMetrics, K6SummaryState, SubgraphRequestStats, SubgraphStats, TrendMetric,
+        TrendValues,
     };
     use crate::resources::ResourceStats;
     use crate::system::SystemInfo;
@@ -397,8 +773,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway A".to_string(),
                     image: "gateway-a:latest".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
             Arc::new(Gateway {
@@ -407,8 +788,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway B".to_string(),
                     image: "gateway-b:v2.0".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
             Arc::new(Gateway {
@@ -417,8 +803,17 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway C".to_string(),
                     image: "gateway-c:experimental".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: Some(crate::config::BuildConfig {
+                        context: std::path::PathBuf::from("builds/gateway-c"),
+                        dockerfile: None,
+                        args: BTreeMap::new(),
+                    }),
                 },
             }),
             Arc::new(Gateway {
@@ -427,8 +822,13 @@ mod tests {
                 config: crate::config::GatewayConfig {
                     label: "Gateway D".to_string(),
                     image: "gateway-d:broken".to_string(),
+                    command: None,
                     args: vec![],
                     env: HashMap::new(),
+                    tags: vec![],
+                    versions: vec![],
+                    version: None,
+                    build: None,
                 },
             }),
         ];
@@ -444,7 +844,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 502 },
+                        subgraph_stats: SubgraphStats {
+                            count: 502,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -480,6 +884,10 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: Some(Correctness {
+                    differences: 0,
+                    excerpt: vec![],
+                }),
             },
             BenchmarkResult {
                 scenario: "simple-query".to_string(),
@@ -491,7 +899,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 502 },
+                        subgraph_stats: SubgraphStats {
+                            count: 502,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -527,6 +939,13 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: Some(Correctness {
+                    differences: 12,
+                    excerpt: vec![
+                        "$.data.users[0].name: expected \"Uri\", got null".to_string(),
+                        "$.data.users[1]: missing".to_string(),
+                    ],
+                }),
             },
             BenchmarkResult {
                 scenario: "complex-nested-query".to_string(),
@@ -538,7 +957,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 502 },
+                        subgraph_stats: SubgraphStats {
+                            count: 502,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: Some(TrendMetric {
                                 values: TrendValues {
@@ -574,6 +997,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
             // Add test case for gateway with no responses
             BenchmarkResult {
@@ -586,7 +1010,11 @@ mod tests {
                         state: K6SummaryState {
                             test_run_duration_ms: 60000.0,
                         },
-                        subgraph_stats: SubgraphStats { count: 0 },
+                        subgraph_stats: SubgraphStats {
+                            count: 0,
+                            ..Default::default()
+                        },
+                        operations: Default::default(),
                         metrics: K6SummaryMetrics {
                             http_req_duration: None,
                             checks: None,
@@ -604,6 +1032,7 @@ mod tests {
                     throttled_time: Duration::from_secs(0),
                     count: 100,
                 },
+                correctness: None,
             },
         ];
 
@@ -624,6 +1053,8 @@ mod tests {
                 supergraph: "test".to_string(),
                 description: "Test scenario for simple GraphQL queries".to_string(),
                 env: HashMap::new(),
+                tags: vec![],
+                load: None,
             },
         );
         scenarios.insert(
@@ -632,6 +1063,8 @@ mod tests {
                 supergraph: "test".to_string(),
                 description: "Test scenario for complex nested GraphQL queries".to_string(),
                 env: HashMap::new(),
+                tags: vec![],
+                load: None,
             },
         );
 
@@ -640,6 +1073,7 @@ mod tests {
             supergraphs: BTreeMap::new(),
             gateways,
             current_dir: std::path::PathBuf::from("/test"),
+            overrides: vec![],
         };
 
         // Use file mode (non-TTY) for test to get full output
@@ -651,7 +1085,7 @@ mod tests {
             &ReportOptions { is_tty: false },
         )
         .unwrap();
-        insta::assert_snapshot!(report, @r"
+        insta::assert_snapshot!(report, @r#"
         # System Information
 
         - Date: 2019-01-01
@@ -668,7 +1102,7 @@ mod tests {
 
         - a: gateway-a:latest
         - b: gateway-b:v2.0
-        - c: gateway-c:experimental
+        - c: gateway-c:experimental (built from `builds/gateway-c`)
         - d-noresponse: gateway-d:broken
 
         # complex-nested-query
@@ -697,10 +1131,10 @@ mod tests {
 
         ![Quality Chart](charts/complex-nested-query-quality.svg)
 
-        | Gateway   | Requests | Failures | Subgraph requests (total) |
-        | :-------- | -------: | -------: | ------------------------: |
-        | Gateway C |      234 |       10 |                2.15 (502) |
-        | Gateway D |        0 |        0 |                     0 (0) |
+        | Gateway   | Requests | Failures | Subgraph requests (total) | Subgraph cache hits |
+        | :-------- | -------: | -------: | ------------------------: | ------------------: |
+        | Gateway C |      234 |       10 |                2.15 (502) |                   - |
+        | Gateway D |        0 |        0 |                     0 (0) |                   - |
 
         # simple-query
 
@@ -728,10 +1162,378 @@ mod tests {
 
         ![Quality Chart](charts/simple-query-quality.svg)
 
-        | Gateway   | Requests | Failures | Subgraph requests (total) |
-        | :-------- | -------: | -------: | ------------------------: |
-        | Gateway A |      251 |        0 |                2.00 (502) |
-        | Gateway B |      234 |        0 |                2.15 (502) |
-        ");
+        | Gateway   | Requests | Failures | Subgraph requests (total) | Subgraph cache hits |
+        | :-------- | -------: | -------: | ------------------------: | ------------------: |
+        | Gateway A |      251 |        0 |                2.00 (502) |                   - |
+        | Gateway B |      234 |        0 |                2.15 (502) |                   - |
+
+        ## Correctness
+
+        | Gateway   | Response         |
+        | :-------- | :--------------- |
+        | Gateway A | correct          |
+        | Gateway B | 12 difference(s) |
+
+        Gateway B:
+
+        ```
+        $.data.users[0].name: expected "Uri", got null
+        $.data.users[1]: missing
+        ```
+        "#);
+    }
+
+    fn gateway(name: &str, label: &str) -> Arc<Gateway> {
+        Arc::new(Gateway {
+            name: name.to_string(),
+            gateways_path: std::path::PathBuf::from("/test/gateways"),
+            config: crate::config::GatewayConfig {
+                label: label.to_string(),
+                image: format!("{name}:latest"),
+                command: None,
+                args: vec![],
+                env: HashMap::new(),
+                tags: vec![],
+                versions: vec![],
+                version: None,
+                build: None,
+            },
+        })
+    }
+
+    /// Result of a steady load of 50 requests/s.
+    fn result(
+        scenario: &str,
+        gateway: &Arc<Gateway>,
+        subgraph_stats: SubgraphStats,
+    ) -> BenchmarkResult {
+        BenchmarkResult {
+            scenario: scenario.to_string(),
+            gateway: gateway.clone(),
+            k6_run: K6Run {
+                start: time::OffsetDateTime::now_utc(),
+                end: time::OffsetDateTime::now_utc(),
+                summary: K6Summary {
+                    state: K6SummaryState {
+                        test_run_duration_ms: 60000.0,
+                    },
+                    subgraph_stats,
+                    operations: BTreeMap::new(),
+                    metrics: K6SummaryMetrics {
+                        http_req_duration: Some(TrendMetric {
+                            values: TrendValues {
+                                count: 3000,
+                                avg: 20.0,
+                                min: 10.0,
+                                med: 19.0,
+                                max: 60.0,
+                                p90: 25.0,
+                                p95: 30.0,
+                                p99: 40.0,
+                            },
+                        }),
+                        checks: Some(CheckMetric {
+                            values: HttpReqFailedValues { fails: 0 },
+                        }),
+                        http_reqs: Some(CounterMetric {
+                            values: CounterValues {
+                                count: 3000.0,
+                                rate: 50.0,
+                            },
+                        }),
+                    },
+                },
+            },
+            resource_stats: ResourceStats {
+                cpu_usage_avg: 0.05,
+                cpu_usage_max: 0.1,
+                cpu_usage_std: 0.01,
+                memory_mib_avg: 200.0,
+                memory_mib_max: 220.0,
+                memory_mib_std: 10.0,
+                throttled_time: Duration::from_secs(0),
+                count: 60,
+            },
+            correctness: None,
+        }
+    }
+
+    fn config(gateways: &[Arc<Gateway>], scenario: &str) -> Config {
+        Config {
+            scenarios: BTreeMap::from([(
+                scenario.to_string(),
+                ScenarioConfig {
+                    supergraph: "test".to_string(),
+                    description: "Test scenario".to_string(),
+                    env: HashMap::new(),
+                    tags: vec![],
+                    load: None,
+                },
+            )]),
+            supergraphs: BTreeMap::new(),
+            gateways: gateways.to_vec(),
+            current_dir: std::path::PathBuf::from("/test"),
+            overrides: vec![],
+        }
+    }
+
+    fn report(results: &[BenchmarkResult], config: &Config) -> String {
+        let system_info = SystemInfo {
+            cpu_model: "Test CPU Model".to_string(),
+            total_memory_mib: 16384,
+            cpu_boost_enabled: None,
+            git_commit: None,
+            linux_version: None,
+            docker_version: None,
+        };
+        generate_report_with_options(
+            time::macros::datetime!(2019-01-01 0:00 UTC),
+            results,
+            &system_info,
+            config,
+            &ReportOptions { is_tty: false },
+        )
+        .unwrap()
+    }
+
+    /// Section of the report starting at the given heading, up to the next one.
+    fn section<'a>(report: &'a str, heading: &str) -> &'a str {
+        let start = report.find(&format!("{heading}\n")).unwrap();
+        let section = &report[start..];
+        let end = section[heading.len()..]
+            .find("\n#")
+            .map_or(section.len(), |end| heading.len() + end);
+        section[..end].trim_end()
+    }
+
+    #[test]
+    fn config_overrides_should_be_listed_in_the_system_information() {
+        let gateways = [gateway("a", "Gateway A")];
+        let mut config = config(&gateways, "query");
+        config.overrides = vec![
+            crate::config::Override::parse("scenarios.query.env.DELAY_MS=20").unwrap(),
+            crate::config::Override::parse("gateways.a.image=a:dev").unwrap(),
+        ];
+        let results = [result("query", &gateways[0], SubgraphStats::default())];
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "# System Information"), @r#"
+        # System Information
+
+        - Date: 2019-01-01
+        - CPU: Test CPU Model
+        - Memory: 16.0 GiB
+        - Config overrides: `scenarios.query.env.DELAY_MS=20`, `gateways.a.image=a:dev`
+        "#);
+    }
+
+    #[test]
+    fn operation_latencies_should_be_listed_per_operation_and_gateway() {
+        let gateways = [gateway("a", "Gateway A"), gateway("b", "Gateway B")];
+        let config = config(&gateways, "mixed");
+        let results = gateways
+            .iter()
+            .zip([1.0, 1.5])
+            .map(|(gateway, factor)| {
+                let mut result = result("mixed", gateway, SubgraphStats::default());
+                let trend = |min: f64| TrendMetric {
+                    values: TrendValues {
+                        count: 1500,
+                        avg: min * factor * 1.5,
+                        min: min * factor,
+                        med: min * factor * 1.4,
+                        max: min * factor * 4.0,
+                        p90: min * factor * 1.8,
+                        p95: min * factor * 2.0,
+                        p99: min * factor * 2.5,
+                    },
+                };
+                result.k6_run.summary.operations = BTreeMap::from([
+                    ("me".to_string(), trend(5.0)),
+                    ("products".to_string(), trend(16.0)),
+                ]);
+                result
+            })
+            .collect::<Vec<_>>();
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "## Operation latencies (ms)"), @r#"
+        ## Operation latencies (ms)
+
+        ![Operations Chart](charts/mixed-operations.svg)
+
+        | Operation | Gateway   |     Min |     Med |     P90 |     P95 |     P99 |     Max |
+        | :-------- | :-------- | ------: | ------: | ------: | ------: | ------: | ------: |
+        | me        | Gateway A |     5.0 |     7.0 |     9.0 |    10.0 |    12.5 |    20.0 |
+        | me        | Gateway B |     7.5 |    10.5 |    13.5 |    15.0 |    18.8 |    30.0 |
+        | products  | Gateway A |    16.0 |    22.4 |    28.8 |    32.0 |    40.0 |    64.0 |
+        | products  | Gateway B |    24.0 |    33.6 |    43.2 |    48.0 |    60.0 |    96.0 |
+        "#);
+    }
+
+    #[test]
+    fn cache_hits_should_be_reported_as_a_share_of_the_cached_subgraph_requests() {
+        let gateways = [gateway("a", "Gateway A"), gateway("b", "Gateway B")];
+        let config = config(&gateways, "query");
+        let results = [
+            result(
+                "query",
+                &gateways[0],
+                SubgraphStats {
+                    count: 6000,
+                    cache_hit: 4500,
+                    cache_miss: 1500,
+                    ..Default::default()
+                },
+            ),
+            result(
+                "query",
+                &gateways[1],
+                SubgraphStats {
+                    count: 9000,
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "## Requests"), @r#"
+        ## Requests
+
+        ![Quality Chart](charts/query-quality.svg)
+
+        | Gateway   | Requests | Failures | Subgraph requests (total) | Subgraph cache hits |
+        | :-------- | -------: | -------: | ------------------------: | ------------------: |
+        | Gateway A |     3000 |        0 |               2.00 (6000) |               75.0% |
+        | Gateway B |     3000 |        0 |               3.00 (9000) |                   - |
+        "#);
+    }
+
+    #[test]
+    fn subgraph_requests_should_be_listed_per_subgraph_and_request() {
+        let gateways = [gateway("a", "Gateway A")];
+        let config = config(&gateways, "query");
+        let results = [result(
+            "query",
+            &gateways[0],
+            SubgraphStats {
+                count: 9000,
+                subgraphs: BTreeMap::from([
+                    (
+                        "accounts".to_string(),
+                        SubgraphRequestStats {
+                            requests: 3000,
+                            root_requests: 3000,
+                            bytes: 102000,
+                            ..Default::default()
+                        },
+                    ),
+                    (
+                        "reviews".to_string(),
+                        SubgraphRequestStats {
+                            requests: 6000,
+                            root_requests: 0,
+                            entities_requests: 6000,
+                            representations: 36000,
+                            bytes: 1344000,
+                            ..Default::default()
+                        },
+                    ),
+                ]),
+                ..Default::default()
+            },
+        )];
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "### Subgraph requests"), @r#"
+        ### Subgraph requests
+
+        | Gateway   | Subgraph |   Requests (total) |     Root | _entities | Representations |   Avg size (B) |
+        | :-------- | :------- | -----------------: | -------: | --------: | --------------: | -------------: |
+        | Gateway A | accounts |        1.00 (3000) |     3000 |         0 |             0.0 |             34 |
+        | Gateway A | reviews  |        2.00 (6000) |        0 |      6000 |             6.0 |            224 |
+        "#);
+    }
+
+    #[test]
+    fn injected_faults_should_be_reported_as_a_share_of_the_subgraph_requests() {
+        let gateways = [gateway("a", "Gateway A"), gateway("b", "Gateway B")];
+        let config = config(&gateways, "partial-failures");
+        let results = [
+            result(
+                "partial-failures",
+                &gateways[0],
+                SubgraphStats {
+                    count: 6000,
+                    faults: BTreeMap::from([
+                        ("http-error".to_string(), 60),
+                        ("partial-null".to_string(), 300),
+                    ]),
+                    ..Default::default()
+                },
+            ),
+            result(
+                "partial-failures",
+                &gateways[1],
+                SubgraphStats {
+                    count: 9000,
+                    faults: BTreeMap::from([("http-error".to_string(), 90)]),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "### Injected subgraph faults"), @r#"
+        ### Injected subgraph faults
+
+        | Gateway   | Fault            |    Count | Share of subgraph requests |
+        | :-------- | :--------------- | -------: | -------------------------: |
+        | Gateway A | http-error       |       60 |                       1.0% |
+        | Gateway A | partial-null     |      300 |                       5.0% |
+        | Gateway B | http-error       |       90 |                       1.0% |
+        "#);
+    }
+
+    #[test]
+    fn subgraph_capacity_should_be_reported_per_subgraph() {
+        let gateways = [gateway("a", "Gateway A")];
+        let config = config(&gateways, "constrained-subgraphs");
+        let results = [result(
+            "constrained-subgraphs",
+            &gateways[0],
+            SubgraphStats {
+                count: 9000,
+                subgraphs: BTreeMap::from([
+                    (
+                        "accounts".to_string(),
+                        SubgraphRequestStats {
+                            requests: 3000,
+                            ..Default::default()
+                        },
+                    ),
+                    (
+                        "reviews".to_string(),
+                        SubgraphRequestStats {
+                            requests: 6000,
+                            queued: 600,
+                            rejected: 30,
+                            ..Default::default()
+                        },
+                    ),
+                ]),
+                ..Default::default()
+            },
+        )];
+
+        let report = report(&results, &config);
+        insta::assert_snapshot!(section(&report, "### Subgraph capacity"), @r#"
+        ### Subgraph capacity
+
+        | Gateway   | Subgraph |           Queued |         Rejected |
+        | :-------- | :------- | ---------------: | ---------------: |
+        | Gateway A | accounts |         0 (0.0%) |         0 (0.0%) |
+        | Gateway A | reviews  |      600 (10.0%) |        30 (0.5%) |
+        "#);
     }
 }
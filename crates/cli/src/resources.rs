@@ -6,7 +6,14 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::task::JoinHandle;
 
-use crate::docker::ContainerId;
+use crate::gateway::GatewayInstance;
+
+/// Interval between two samples of a process gateway, similar to docker stats.
+const PROCESS_SAMPLING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Clock ticks per second used by /proc/<pid>/stat (USER_HZ), 100 on all mainstream Linux
+/// architectures.
+const CLOCK_TICKS_PER_SECOND: u64 = 100;
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceStats {
@@ -20,16 +27,30 @@ pub struct ResourceStats {
     pub count: usize,
 }
 
-pub struct DockerStatsCollector {
+/// Collects the resource usage of a gateway, from docker stats for containers and from /proc for
+/// processes.
+pub struct StatsCollector {
     is_collecting: Arc<AtomicBool>,
     handle: Option<JoinHandle<Vec<StatSample>>>,
 }
 
-impl DockerStatsCollector {
-    pub async fn start(docker: Docker, container_id: &ContainerId) -> anyhow::Result<Self> {
+impl StatsCollector {
+    pub async fn start(docker: Docker, gateway: &GatewayInstance) -> anyhow::Result<Self> {
         let is_collecting = Arc::new(AtomicBool::new(true));
         let is_collecting_clone = is_collecting.clone();
 
+        let container_id = match gateway {
+            GatewayInstance::Container(container_id) => container_id,
+            GatewayInstance::Process(_) => {
+                let pid = gateway.pid().expect("process gateways have a PID");
+                let handle = tokio::spawn(sample_process_group(pid, is_collecting_clone));
+                return Ok(Self {
+                    is_collecting,
+                    handle: Some(handle),
+                });
+            }
+        };
+
         let mut samples = Vec::new();
         let mut stats_stream = docker.stats(
             container_id,
@@ -95,8 +116,15 @@ impl DockerStatsCollector {
 
             stats.count += 1;
 
-            let cpu_usage = (sample.cpu_total_usage - sample.precpu_total_usage)
-                .div_duration_f64((sample.read - sample.preread).try_into().unwrap());
+            // The CPU time of a process group goes down when one of its processes exits.
+            let cpu_usage = (sample
+                .cpu_total_usage
+                .saturating_sub(sample.precpu_total_usage))
+            .div_duration_f64(
+                (sample.read - sample.preread)
+                    .try_into()
+                    .expect("read is after preread"),
+            );
             cpu_values.push(cpu_usage);
             stats.cpu_usage_avg += (cpu_usage - stats.cpu_usage_avg) / (stats.count as f64);
             stats.cpu_usage_max = stats.cpu_usage_max.max(cpu_usage);
@@ -124,6 +152,121 @@ impl DockerStatsCollector {
     }
 }
 
+/// Sample the CPU time and memory of all the processes in the process group of `pid`, until
+/// collection stops.
+async fn sample_process_group(pid: u32, is_collecting: Arc<AtomicBool>) -> Vec<StatSample> {
+    let mut samples = Vec::new();
+    let mut previous = (
+        time::OffsetDateTime::now_utc(),
+        sample_process_group_usage(pid).await.0,
+    );
+
+    while is_collecting.load(Ordering::SeqCst) {
+        tokio::time::sleep(PROCESS_SAMPLING_INTERVAL).await;
+
+        let read = time::OffsetDateTime::now_utc();
+        let (cpu_total_usage, memory_bytes) = sample_process_group_usage(pid).await;
+        samples.push(StatSample {
+            preread: previous.0,
+            read,
+            precpu_total_usage: previous.1,
+            cpu_total_usage,
+            memory_bytes,
+            throttled_time: None,
+        });
+        previous = (read, cpu_total_usage);
+    }
+
+    samples
+}
+
+/// Read the usage of the process group off the async runtime, as it walks through all of `/proc`.
+async fn sample_process_group_usage(pgid: u32) -> (Duration, u64) {
+    tokio::task::spawn_blocking(move || process_group_usage(pgid))
+        .await
+        .unwrap_or((Duration::ZERO, 0))
+}
+
+/// Total CPU time and resident memory of the processes in the process group `pgid`. CPU time of
+/// terminated children is accounted for through the group leader.
+fn process_group_usage(pgid: u32) -> (Duration, u64) {
+    let mut cpu_ticks = 0;
+    let mut memory_bytes = 0;
+
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return (Duration::ZERO, 0);
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        // Processes may exit at any time, so read errors are expected.
+        let Some(stat) = std::fs::read_to_string(entry.path().join("stat"))
+            .ok()
+            .and_then(|content| ProcStat::parse(&content))
+        else {
+            continue;
+        };
+        if stat.pgrp != pgid {
+            continue;
+        }
+
+        cpu_ticks += stat.cpu_ticks;
+        if pid == pgid {
+            cpu_ticks += stat.children_cpu_ticks;
+        }
+        memory_bytes += std::fs::read_to_string(entry.path().join("status"))
+            .ok()
+            .and_then(|content| parse_vm_rss(&content))
+            .unwrap_or_default();
+    }
+
+    (
+        Duration::from_nanos(cpu_ticks * 1_000_000_000 / CLOCK_TICKS_PER_SECOND),
+        memory_bytes,
+    )
+}
+
+/// The fields we need from /proc/<pid>/stat.
+#[derive(Debug, PartialEq)]
+struct ProcStat {
+    pgrp: u32,
+    /// User and system time
+    cpu_ticks: u64,
+    /// User and system time of waited-for children
+    children_cpu_ticks: u64,
+}
+
+impl ProcStat {
+    fn parse(content: &str) -> Option<Self> {
+        // The command name may contain spaces and parentheses, fields start after the last ')'.
+        // The first one is the 3rd field of proc(5).
+        let fields: Vec<&str> = content[content.rfind(')')? + 1..]
+            .split_whitespace()
+            .collect();
+        let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+
+        Some(Self {
+            pgrp: field(5)? as u32,
+            cpu_ticks: field(14)? + field(15)?,
+            children_cpu_ticks: field(16)? + field(17)?,
+        })
+    }
+}
+
+/// Resident memory in bytes from /proc/<pid>/status.
+fn parse_vm_rss(content: &str) -> Option<u64> {
+    let line = content.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StatSample {
     preread: time::OffsetDateTime,
@@ -289,3 +432,30 @@ impl TryFrom<ContainerStatsResponse> for StatSample {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_proc_stat_and_status() {
+        let content = "4242 (grafbase (gw)) S 1 4242 4242 0 -1 4194560 2155 0 0 0 120 30 7 3 20 0 8 0 123456 1073741824 5000 18446744073709551615 1 1 0 0 0 0 0 4096 0 0 0 0 17 3 0 0 0 0 0";
+        insta::assert_debug_snapshot!(ProcStat::parse(content), @r#"
+        Some(
+            ProcStat {
+                pgrp: 4242,
+                cpu_ticks: 150,
+                children_cpu_ticks: 10,
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(ProcStat::parse("4242 (truncated"), @"None");
+
+        let status = "Name:\tgrafbase\nVmPeak:\t  200000 kB\nVmRSS:\t  102400 kB\nThreads:\t8\n";
+        insta::assert_debug_snapshot!(parse_vm_rss(status), @r#"
+        Some(
+            104857600,
+        )
+        "#);
+    }
+}
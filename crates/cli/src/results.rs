@@ -0,0 +1,222 @@
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    benchmark::{Benchmark, BenchmarkResult},
+    config::Config,
+    correctness::Correctness,
+    k6::{self, K6Run},
+    resources::ResourceStats,
+};
+
+/// File of a run directory storing the settings it was started with.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Directory in which the results of a single run are persisted as they complete, allowing an
+/// interrupted run to be resumed.
+#[derive(Debug)]
+pub struct RunDir {
+    path: PathBuf,
+}
+
+/// Settings the results of a run depend on, which a resumed run must share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSettings {
+    /// K6 test duration override, e.g. `1m 30s`.
+    pub duration: Option<String>,
+    /// Overrides applied on top of config.toml, as `key.path=value`.
+    pub overrides: Vec<String>,
+}
+
+impl RunSettings {
+    pub fn new(config: &Config, duration: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            duration: duration
+                .map(|duration| k6::parse_duration(duration).map(k6::format_duration))
+                .transpose()?,
+            overrides: config.overrides.iter().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+impl std::fmt::Display for RunSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "duration {}, overrides [{}]",
+            self.duration.as_deref().unwrap_or("default"),
+            self.overrides.join(", ")
+        )
+    }
+}
+
+/// On-disk representation of a completed benchmark.
+#[derive(Serialize, Deserialize)]
+struct StoredResult {
+    scenario: String,
+    gateway: String,
+    k6_run: K6Run,
+    resource_stats: ResourceStats,
+    #[serde(default)]
+    correctness: Option<Correctness>,
+}
+
+impl RunDir {
+    /// Create a new run directory under `results/` named after the current time, with a suffix
+    /// if another run started within the same second.
+    pub fn create(current_dir: &Path, settings: &RunSettings) -> Result<Self> {
+        let now = time::OffsetDateTime::now_utc();
+        let name = format!(
+            "{}T{:02}-{:02}-{:02}Z",
+            now.date(),
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+        let results_dir = current_dir.join("results");
+        std::fs::create_dir_all(&results_dir)
+            .with_context(|| format!("Could not create results directory {:?}", results_dir))?;
+
+        let mut path = results_dir.join(&name);
+        let mut attempt = 1;
+        loop {
+            match std::fs::create_dir(&path) {
+                Ok(()) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    attempt += 1;
+                    path = results_dir.join(format!("{name}-{attempt}"));
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Could not create results directory {:?}", path));
+                }
+            }
+        }
+
+        let settings_path = path.join(SETTINGS_FILE);
+        std::fs::write(&settings_path, serde_json::to_vec_pretty(settings)?)
+            .with_context(|| format!("Could not write {:?}", settings_path))?;
+        Ok(Self { path })
+    }
+
+    /// Open an existing run directory to resume it, which must have been started with the same
+    /// settings.
+    pub fn open(path: &Path, settings: &RunSettings) -> Result<Self> {
+        if !path.is_dir() {
+            return Err(anyhow::anyhow!("Run directory {:?} does not exist", path));
+        }
+
+        let settings_path = path.join(SETTINGS_FILE);
+        let content = std::fs::read_to_string(&settings_path)
+            .with_context(|| format!("Could not read the settings of the run {:?}", path))?;
+        let stored: RunSettings = serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse {:?}", settings_path))?;
+        if stored != *settings {
+            return Err(anyhow::anyhow!(
+                "Run {:?} was started with {}, it can't be resumed with {}",
+                path,
+                stored,
+                settings
+            ));
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Open the run directory to resume if any, or create a new one.
+    pub fn create_or_resume(
+        current_dir: &Path,
+        resume: Option<&Path>,
+        settings: &RunSettings,
+    ) -> Result<Self> {
+        match resume {
+            Some(path) => Self::open(path, settings),
+            None => Self::create(current_dir, settings),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn result_path(&self, scenario: &str, gateway: &str) -> PathBuf {
+        self.path.join(format!("{scenario}--{gateway}.json"))
+    }
+
+    /// Whether the benchmark was already completed in this run.
+    pub fn is_completed(&self, benchmark: &Benchmark) -> bool {
+        self.result_path(benchmark.name(), benchmark.gateway().name())
+            .exists()
+    }
+
+    /// Persist a completed benchmark result.
+    pub fn save(&self, result: &BenchmarkResult) -> Result<()> {
+        let stored = StoredResult {
+            scenario: result.scenario.clone(),
+            gateway: result.gateway.name().to_string(),
+            k6_run: result.k6_run.clone(),
+            resource_stats: result.resource_stats.clone(),
+            correctness: result.correctness.clone(),
+        };
+        let path = self.result_path(&stored.scenario, &stored.gateway);
+        // Write to a temporary file first so that an interruption never leaves a partial result.
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&stored)?)?;
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Could not write result to {:?}", path))?;
+        Ok(())
+    }
+
+    /// Load the stored result of a benchmark completed in this run, if any.
+    pub fn load(&self, benchmark: &Benchmark) -> Result<Option<BenchmarkResult>> {
+        let path = self.result_path(benchmark.name(), benchmark.gateway().name());
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let stored: StoredResult = serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse stored result {:?}", path))?;
+
+        Ok(Some(BenchmarkResult {
+            scenario: stored.scenario,
+            gateway: benchmark.gateway().clone(),
+            k6_run: stored.k6_run,
+            resource_stats: stored.resource_stats,
+            correctness: stored.correctness,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_must_not_share_a_directory_nor_be_resumed_with_other_settings() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let settings = RunSettings {
+            duration: Some("30s".to_string()),
+            overrides: vec!["scenarios.query.env.DELAY_MS=20".to_string()],
+        };
+
+        let first = RunDir::create(dir, &settings).unwrap();
+        let second = RunDir::create(dir, &settings).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(RunDir::open(first.path(), &settings).is_ok());
+
+        let other = RunSettings {
+            duration: None,
+            ..settings
+        };
+        let err = RunDir::open(first.path(), &other)
+            .unwrap_err()
+            .to_string()
+            .replace(&first.path().to_string_lossy().to_string(), "<run>");
+        insta::assert_snapshot!(err, @r#"Run "<run>" was started with duration 30s, overrides [scenarios.query.env.DELAY_MS=20], it can't be resumed with duration default, overrides [scenarios.query.env.DELAY_MS=20]"#);
+    }
+}
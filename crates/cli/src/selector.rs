@@ -0,0 +1,186 @@
+use anyhow::Result;
+use fast_glob::glob_match;
+
+/// Prefix of selectors matching on tags rather than names.
+const TAG_PREFIX: &str = "tag:";
+
+/// Selects gateways or scenarios either by name, supporting globs like `*-no-cache`, or by tag
+/// declared in `config.toml` with `tag:<tag>`.
+#[derive(Debug, Clone)]
+pub enum Selector {
+    Name(String),
+    Tag(String),
+}
+
+impl Selector {
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.strip_prefix(TAG_PREFIX) {
+            Some(tag) => Selector::Tag(tag.to_string()),
+            None => Selector::Name(value.to_lowercase()),
+        }
+    }
+
+    /// Parse a comma-separated list of selectors, ignoring empty ones.
+    pub fn parse_list(value: &str) -> Vec<Self> {
+        value
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    pub fn matches(&self, name: &str, tags: &[String]) -> bool {
+        match self {
+            // Versions of a gateway, named `<name>@<version>`, also match the name of the gateway.
+            Selector::Name(pattern) => {
+                glob_match(pattern, name)
+                    || name
+                        .split_once('@')
+                        .is_some_and(|(base_name, _)| glob_match(pattern, base_name))
+            }
+            Selector::Tag(pattern) => tags.iter().any(|tag| glob_match(pattern, tag)),
+        }
+    }
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selector::Name(pattern) => write!(f, "{pattern}"),
+            Selector::Tag(pattern) => write!(f, "{TAG_PREFIX}{pattern}"),
+        }
+    }
+}
+
+/// Names of the candidates matching any of the `include` selectors, or all of them if there are
+/// none, and none of the `exclude` ones. Every `include` selector must match at least one
+/// candidate to catch typos early.
+pub fn select<'a>(
+    kind: &str,
+    candidates: impl IntoIterator<Item = (&'a str, &'a [String])>,
+    include: &[Selector],
+    exclude: &[Selector],
+) -> Result<Vec<String>> {
+    let candidates: Vec<(&str, &[String])> = candidates.into_iter().collect();
+
+    for selector in include {
+        if !candidates
+            .iter()
+            .any(|(name, tags)| selector.matches(name, tags))
+        {
+            return Err(anyhow::anyhow!(
+                "No {} matches '{}' in config.toml",
+                kind,
+                selector
+            ));
+        }
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|(name, tags)| {
+            include.is_empty() || include.iter().any(|selector| selector.matches(name, tags))
+        })
+        .filter(|(name, tags)| !exclude.iter().any(|selector| selector.matches(name, tags)))
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<(&'static str, Vec<String>)> {
+        vec![
+            ("grafbase", vec!["rust".to_string()]),
+            (
+                "grafbase-no-cache",
+                vec!["rust".to_string(), "caching-disabled".to_string()],
+            ),
+            (
+                "cosmo-no-cache",
+                vec!["go".to_string(), "caching-disabled".to_string()],
+            ),
+            ("hive-gateway", vec!["node".to_string()]),
+        ]
+    }
+
+    fn run(include: &str, exclude: &str) -> Result<Vec<String>> {
+        let candidates = candidates();
+        select(
+            "gateway",
+            candidates
+                .iter()
+                .map(|(name, tags)| (*name, tags.as_slice())),
+            &Selector::parse_list(include),
+            &Selector::parse_list(exclude),
+        )
+    }
+
+    #[test]
+    fn should_select_by_name_glob_and_tag_minus_exclusions() {
+        let selections: Vec<String> = [
+            ("", ""),
+            ("grafbase", ""),
+            ("*-no-cache", ""),
+            ("tag:rust", "tag:caching-disabled"),
+            ("hive-gateway, tag:caching-disabled", "cosmo*"),
+            ("unknown", ""),
+            ("tag:unknown", ""),
+        ]
+        .into_iter()
+        .map(|(include, exclude)| match run(include, exclude) {
+            Ok(names) => format!("{include:?} - {exclude:?}: {}", names.join(", ")),
+            Err(e) => format!("{include:?} - {exclude:?}: {e}"),
+        })
+        .collect();
+        insta::assert_snapshot!(selections.join("\n"), @r#"
+        "" - "": grafbase, grafbase-no-cache, cosmo-no-cache, hive-gateway
+        "grafbase" - "": grafbase
+        "*-no-cache" - "": grafbase-no-cache, cosmo-no-cache
+        "tag:rust" - "tag:caching-disabled": grafbase
+        "hive-gateway, tag:caching-disabled" - "cosmo*": grafbase-no-cache, hive-gateway
+        "unknown" - "": No gateway matches 'unknown' in config.toml
+        "tag:unknown" - "": No gateway matches 'tag:unknown' in config.toml
+        "#);
+    }
+
+    #[test]
+    fn versions_should_be_selected_by_their_name_or_the_gateway_name() {
+        let tags = vec!["rust".to_string()];
+        let candidates = [
+            ("grafbase@0.48.0", tags.as_slice()),
+            ("grafbase@main", tags.as_slice()),
+            ("cosmo", &[]),
+        ];
+        let selections: Vec<String> = [
+            ("grafbase", ""),
+            ("grafbase@0.48.0", ""),
+            ("tag:rust", "grafbase@main"),
+            ("grafbase@*", ""),
+            ("grafbase@0.47.0", ""),
+        ]
+        .into_iter()
+        .map(|(include, exclude)| {
+            let selected = select(
+                "gateway",
+                candidates,
+                &Selector::parse_list(include),
+                &Selector::parse_list(exclude),
+            );
+            match selected {
+                Ok(names) => format!("{include:?} - {exclude:?}: {}", names.join(", ")),
+                Err(e) => format!("{include:?} - {exclude:?}: {e}"),
+            }
+        })
+        .collect();
+        insta::assert_snapshot!(selections.join("\n"), @r#"
+        "grafbase" - "": grafbase@0.48.0, grafbase@main
+        "grafbase@0.48.0" - "": grafbase@0.48.0
+        "tag:rust" - "grafbase@main": grafbase@0.48.0
+        "grafbase@*" - "": grafbase@0.48.0, grafbase@main
+        "grafbase@0.47.0" - "": No gateway matches 'grafbase@0.47.0' in config.toml
+        "#);
+    }
+}
@@ -0,0 +1,466 @@
+use anyhow::{Context as _, Result};
+use async_graphql_parser::{
+    Positioned,
+    types::{ConstDirective, TypeKind, TypeSystemDefinition},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use crate::{
+    config::{Config, SupergraphConfig},
+    docker,
+};
+
+/// A subgraph of a composed supergraph, as declared by its `join__Graph` enum value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinGraph {
+    /// Enum value referenced by the `@join__*` directives, e.g. `ACCOUNTS`.
+    pub enum_value: String,
+    pub name: String,
+    pub url: String,
+}
+
+impl JoinGraph {
+    /// URL of the `/sdl/{name}` endpoint of the subgraph service, next to its `/graphql/{name}`
+    /// endpoint.
+    pub fn sdl_url(&self) -> String {
+        match self.url.rsplit_once("/graphql/") {
+            Some((base, name)) => format!("{base}/sdl/{name}"),
+            None => format!("{}/sdl/{}", self.url.trim_end_matches('/'), self.name),
+        }
+    }
+}
+
+/// Supergraphs selected by name, all of them if none is given.
+pub fn select<'a>(
+    config: &'a Config,
+    names: &'a [String],
+) -> Result<Vec<(&'a String, &'a SupergraphConfig)>> {
+    if names.is_empty() {
+        return Ok(config.supergraphs.iter().collect());
+    }
+    names
+        .iter()
+        .map(|name| Ok((name, config.get_supergraph(name)?)))
+        .collect()
+}
+
+/// Start the compose services serving the subgraphs of the given supergraphs.
+pub async fn start_subgraphs(
+    docker: &bollard::Docker,
+    config: &Config,
+    supergraphs: &[(&String, &SupergraphConfig)],
+) -> Result<()> {
+    let ports: Vec<u16> = supergraphs.iter().map(|(_, s)| s.port).collect();
+    docker::cleanup(docker, &ports).await?;
+
+    let mut services: Vec<String> = supergraphs
+        .iter()
+        .flat_map(|(_, s)| s.subgraphs.iter().cloned())
+        .collect();
+    services.sort();
+    services.dedup();
+    docker::compose_up(&config.current_dir, &services, &Default::default())
+}
+
+/// Fetch the SDL served by a running subgraph.
+pub async fn fetch_sdl(client: &reqwest::Client, graph: &JoinGraph) -> Result<String> {
+    let sdl_url = graph.sdl_url();
+    let response = client
+        .get(&sdl_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch the schema of '{}'", graph.name))?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to fetch the schema of '{}' from {}: {}",
+            graph.name,
+            sdl_url,
+            response.status()
+        ));
+    }
+    Ok(response.text().await?)
+}
+
+/// Fetch the SDL of every subgraph served by the compose service listening on `port`, by name.
+pub async fn fetch_service_sdls(
+    client: &reqwest::Client,
+    port: u16,
+) -> Result<BTreeMap<String, String>> {
+    let sdl_url = format!("http://localhost:{port}/sdl");
+    let response = client
+        .get(&sdl_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch the subgraph schemas from {sdl_url}"))?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to fetch the subgraph schemas from {}: {}",
+            sdl_url,
+            response.status()
+        ));
+    }
+    let body = response.text().await?;
+    serde_json::from_str(&body).with_context(|| {
+        format!("Expected the schemas of named subgraphs from {sdl_url}, keyed by name")
+    })
+}
+
+/// Read the subgraphs of the committed supergraph schema.
+pub fn read_join_graphs(supergraph_dir: &Path) -> Result<Vec<JoinGraph>> {
+    let path = supergraph_dir.join("schema.graphql");
+    let schema =
+        std::fs::read_to_string(&path).with_context(|| format!("Could not read {:?}", path))?;
+    let graphs = join_graphs(&schema);
+    if graphs.is_empty() {
+        return Err(anyhow::anyhow!("No join__graph found in {:?}", path));
+    }
+    Ok(graphs)
+}
+
+/// Subgraphs declared with `@join__graph(name: "...", url: "...")` in a supergraph schema.
+pub fn join_graphs(schema: &str) -> Vec<JoinGraph> {
+    let mut graphs = Vec::new();
+
+    for (start, _) in schema.match_indices("@join__graph(") {
+        // Skip the directive definition itself
+        let before = schema[..start].trim_end();
+        if before.ends_with("directive") {
+            continue;
+        }
+        let Some(enum_value) = before
+            .rsplit(|c: char| c.is_whitespace() || c == '{')
+            .next()
+            .filter(|value| !value.is_empty())
+        else {
+            continue;
+        };
+
+        let arguments = &schema[start + "@join__graph(".len()..];
+        let Some(end) = arguments.find(')') else {
+            continue;
+        };
+        let arguments = &arguments[..end];
+
+        if let (Some(name), Some(url)) = (
+            string_argument(arguments, "name"),
+            string_argument(arguments, "url"),
+        ) {
+            graphs.push(JoinGraph {
+                enum_value: enum_value.to_string(),
+                name,
+                url,
+            });
+        }
+    }
+
+    graphs
+}
+
+/// Value of a string argument like `name: "accounts"`.
+fn string_argument(arguments: &str, name: &str) -> Option<String> {
+    let mut rest = arguments;
+    while let Some(position) = rest.find(name) {
+        let is_word_start = rest[..position]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric() && c != '_');
+        let after = rest[position + name.len()..].trim_start();
+        if is_word_start && let Some(value) = after.strip_prefix(':') {
+            let value = value.trim_start().strip_prefix('"')?;
+            return value.find('"').map(|end| value[..end].to_string());
+        }
+        rest = &rest[position + name.len()..];
+    }
+    None
+}
+
+/// Types of a schema with their fields, enum values or union members.
+pub type Definitions = BTreeMap<String, BTreeSet<String>>;
+
+/// Definitions the supergraph attributes to a subgraph through its `@join__*` directives.
+pub fn supergraph_definitions(schema: &str, graph: &JoinGraph) -> Result<Definitions> {
+    let document = async_graphql_parser::parse_schema(schema)?;
+    let mut definitions = Definitions::new();
+
+    for definition in document.definitions {
+        let TypeSystemDefinition::Type(ty) = definition else {
+            continue;
+        };
+        let ty = ty.node;
+        let type_graphs = join_graphs_of(&ty.directives, "join__type");
+        if !type_graphs.contains(&graph.enum_value) {
+            continue;
+        }
+
+        // Members without any `@join__*` directive belong to all the graphs of their type.
+        let in_graph = |directives: &[Positioned<ConstDirective>], directive: &str| {
+            let graphs = join_graphs_of(directives, directive);
+            graphs.is_empty() || graphs.contains(&graph.enum_value)
+        };
+
+        let members = match ty.kind {
+            TypeKind::Object(object) => object
+                .fields
+                .into_iter()
+                .filter(|f| in_graph(&f.node.directives, "join__field"))
+                .map(|f| f.node.name.node.to_string())
+                .collect(),
+            TypeKind::Interface(interface) => interface
+                .fields
+                .into_iter()
+                .filter(|f| in_graph(&f.node.directives, "join__field"))
+                .map(|f| f.node.name.node.to_string())
+                .collect(),
+            TypeKind::InputObject(input) => input
+                .fields
+                .into_iter()
+                .filter(|f| in_graph(&f.node.directives, "join__field"))
+                .map(|f| f.node.name.node.to_string())
+                .collect(),
+            TypeKind::Enum(enum_type) => enum_type
+                .values
+                .into_iter()
+                .filter(|v| in_graph(&v.node.directives, "join__enumValue"))
+                .map(|v| v.node.value.node.to_string())
+                .collect(),
+            TypeKind::Union(union) => {
+                let members = join_union_members(&ty.directives, &graph.enum_value);
+                if members.is_empty() {
+                    union
+                        .members
+                        .into_iter()
+                        .map(|m| m.node.to_string())
+                        .collect()
+                } else {
+                    members
+                }
+            }
+            TypeKind::Scalar => BTreeSet::new(),
+        };
+        definitions
+            .entry(ty.name.node.to_string())
+            .or_default()
+            .extend(members);
+    }
+
+    Ok(definitions)
+}
+
+/// Definitions of a subgraph SDL, without the federation additions.
+pub fn subgraph_definitions(sdl: &str) -> Result<Definitions> {
+    let document = async_graphql_parser::parse_schema(sdl)?;
+    let mut definitions = Definitions::new();
+
+    for definition in document.definitions {
+        let TypeSystemDefinition::Type(ty) = definition else {
+            continue;
+        };
+        let ty = ty.node;
+        let name = ty.name.node.to_string();
+        if is_federation_type(&name) {
+            continue;
+        }
+
+        let members: BTreeSet<String> = match ty.kind {
+            TypeKind::Object(object) => object
+                .fields
+                .into_iter()
+                .map(|f| f.node.name.node.to_string())
+                .filter(|name| name != "_service" && name != "_entities")
+                .collect(),
+            TypeKind::Interface(interface) => interface
+                .fields
+                .into_iter()
+                .map(|f| f.node.name.node.to_string())
+                .collect(),
+            TypeKind::InputObject(input) => input
+                .fields
+                .into_iter()
+                .map(|f| f.node.name.node.to_string())
+                .collect(),
+            TypeKind::Enum(enum_type) => enum_type
+                .values
+                .into_iter()
+                .map(|v| v.node.value.node.to_string())
+                .collect(),
+            TypeKind::Union(union) => union
+                .members
+                .into_iter()
+                .map(|m| m.node.to_string())
+                .collect(),
+            TypeKind::Scalar => BTreeSet::new(),
+        };
+        definitions.entry(name).or_default().extend(members);
+    }
+
+    Ok(definitions)
+}
+
+fn is_federation_type(name: &str) -> bool {
+    name.starts_with('_')
+        || name.starts_with("link__")
+        || name.starts_with("join__")
+        || name.starts_with("federation__")
+}
+
+/// Graphs referenced by the `graph` argument of the given directive.
+fn join_graphs_of(directives: &[Positioned<ConstDirective>], directive: &str) -> Vec<String> {
+    directives
+        .iter()
+        .filter(|d| d.node.name.node == directive)
+        .filter_map(|d| d.node.get_argument("graph"))
+        .map(|graph| graph.node.to_string())
+        .collect()
+}
+
+/// Members of a union within a graph, from its `@join__unionMember` directives.
+fn join_union_members(directives: &[Positioned<ConstDirective>], graph: &str) -> BTreeSet<String> {
+    directives
+        .iter()
+        .filter(|d| d.node.name.node == "join__unionMember")
+        .filter(|d| {
+            d.node
+                .get_argument("graph")
+                .is_some_and(|g| g.node.to_string() == graph)
+        })
+        .filter_map(|d| d.node.get_argument("member"))
+        .map(|member| member.node.to_string().trim_matches('"').to_string())
+        .collect()
+}
+
+/// Differences between the definitions the supergraph expects from a subgraph and the ones it
+/// actually serves.
+#[derive(Debug, Default, PartialEq)]
+pub struct SchemaDiff {
+    /// Definitions the supergraph relies on which the subgraph doesn't serve.
+    pub missing: Vec<String>,
+    /// Definitions the subgraph serves which the supergraph doesn't expose. Subgraph services
+    /// serving a single schema for all their subgraphs always have some.
+    pub extra: Vec<String>,
+}
+
+pub fn diff_definitions(expected: &Definitions, served: &Definitions) -> SchemaDiff {
+    let mut diff = SchemaDiff::default();
+
+    for (name, members) in expected {
+        let Some(served_members) = served.get(name) else {
+            // Composition adds every subgraph to the root types, even without any field.
+            if !(members.is_empty() && ROOT_TYPES.contains(&name.as_str())) {
+                diff.missing.push(format!("type '{name}'"));
+            }
+            continue;
+        };
+        for member in members.difference(served_members) {
+            diff.missing.push(format!("'{name}.{member}'"));
+        }
+        for member in served_members.difference(members) {
+            diff.extra.push(format!("'{name}.{member}'"));
+        }
+    }
+
+    for name in served.keys().filter(|name| !expected.contains_key(*name)) {
+        diff.extra.push(format!("type '{name}'"));
+    }
+
+    diff
+}
+
+const ROOT_TYPES: [&str; 3] = ["Query", "Mutation", "Subscription"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_join_graphs_spanning_multiple_lines() {
+        let schema = r#"
+directive @join__graph(name: String!, url: String!) on ENUM_VALUE
+
+enum join__Graph {
+  ACCOUNTS
+    @join__graph(
+      name: "accounts"
+      url: "http://localhost:7200/graphql/accounts"
+    )
+  REVIEWS @join__graph(name: "reviews", url: "http://localhost:7200/graphql/reviews")
+}
+"#;
+
+        let graphs = join_graphs(schema);
+        insta::assert_debug_snapshot!(graphs, @r#"
+        [
+            JoinGraph {
+                enum_value: "ACCOUNTS",
+                name: "accounts",
+                url: "http://localhost:7200/graphql/accounts",
+            },
+            JoinGraph {
+                enum_value: "REVIEWS",
+                name: "reviews",
+                url: "http://localhost:7200/graphql/reviews",
+            },
+        ]
+        "#);
+        insta::assert_snapshot!(graphs[0].sdl_url(), @"http://localhost:7200/sdl/accounts");
+    }
+
+    #[test]
+    fn should_diff_supergraph_definitions_against_the_subgraph_sdl() {
+        let schema = r#"
+enum join__Graph {
+  A @join__graph(name: "a", url: "http://localhost:7000/graphql/a")
+  B @join__graph(name: "b", url: "http://localhost:7000/graphql/b")
+}
+
+type Query @join__type(graph: A) @join__type(graph: B) {
+  user: User @join__field(graph: A)
+}
+
+type User @join__type(graph: A, key: "id") @join__type(graph: B, key: "id") {
+  id: ID!
+  name: String @join__field(graph: A)
+  reviews: [String!] @join__field(graph: B)
+}
+"#;
+        let graphs = join_graphs(schema);
+        let expected = supergraph_definitions(schema, &graphs[1]).unwrap();
+        insta::assert_debug_snapshot!(expected, @r#"
+        {
+            "Query": {},
+            "User": {
+                "id",
+                "reviews",
+            },
+        }
+        "#);
+
+        let sdl = r#"
+type User @key(fields: "id") {
+  id: ID!
+  rating: Int
+}
+
+type Query {
+  _service: _Service!
+}
+
+type _Service {
+  sdl: String
+}
+"#;
+        let served = subgraph_definitions(sdl).unwrap();
+        insta::assert_debug_snapshot!(diff_definitions(&expected, &served), @r#"
+        SchemaDiff {
+            missing: [
+                "'User.reviews'",
+            ],
+            extra: [
+                "'User.rating'",
+            ],
+        }
+        "#);
+    }
+}
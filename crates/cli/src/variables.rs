@@ -0,0 +1,114 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Value of a GraphQL variable generated for each request, defeating response caching and
+/// request deduplication:
+///
+/// ```toml
+/// generators.id = { type = "sequence", start = 1, end = 6 }
+/// generators.first = { type = "range", min = 1, max = 5 }
+/// generators.locale = { type = "pick", values = ["en", "fr"] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Generator {
+    /// Integers from `start` to `end` included, following the iteration number of the test and
+    /// starting over once `end` is reached.
+    Sequence {
+        #[serde(default)]
+        start: i64,
+        end: i64,
+    },
+    /// Random integer between `min` and `max` included.
+    Range { min: i64, max: i64 },
+    /// Random value from the list.
+    Pick { values: Vec<toml::Value> },
+}
+
+impl Generator {
+    /// JavaScript expression evaluated by K6 for each request.
+    pub fn expression(&self) -> Result<String> {
+        match self {
+            Generator::Sequence { start, end } => {
+                if end < start {
+                    return Err(anyhow::anyhow!(
+                        "Sequence end {} is lower than its start {}",
+                        end,
+                        start
+                    ));
+                }
+                let count = value_count(*start, *end).ok_or_else(|| {
+                    anyhow::anyhow!("Sequence from {start} to {end} is too large")
+                })?;
+                Ok(format!(
+                    "{start} + (exec.scenario.iterationInTest % {count})"
+                ))
+            }
+            Generator::Range { min, max } => {
+                if max < min {
+                    return Err(anyhow::anyhow!(
+                        "Range max {} is lower than its min {}",
+                        max,
+                        min
+                    ));
+                }
+                let count = value_count(*min, *max)
+                    .ok_or_else(|| anyhow::anyhow!("Range from {min} to {max} is too large"))?;
+                Ok(format!("{min} + Math.floor(Math.random() * {count})"))
+            }
+            Generator::Pick { values } => {
+                if values.is_empty() {
+                    return Err(anyhow::anyhow!("Pick generator has no values"));
+                }
+                let values = serde_json::to_string(values)?;
+                Ok(format!("pick({values})"))
+            }
+        }
+    }
+}
+
+/// Number of integers from `from` to `to` included, if it doesn't overflow.
+fn value_count(from: i64, to: i64) -> Option<i64> {
+    to.checked_sub(from)?.checked_add(1)
+}
+
+/// JavaScript object literal with the generated value of each variable.
+pub fn generated_variables(generators: &BTreeMap<String, Generator>) -> Result<String> {
+    let mut variables = Vec::with_capacity(generators.len());
+    for (name, generator) in generators {
+        let expression = generator
+            .expression()
+            .map_err(|e| anyhow::anyhow!("Invalid generator for variable '{}': {}", name, e))?;
+        variables.push(format!("{}: {expression}", serde_json::to_string(name)?));
+    }
+    Ok(format!("{{ {} }}", variables.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_a_javascript_expression_per_variable() {
+        let generators: BTreeMap<String, Generator> = toml::from_str(
+            r#"
+id = { type = "sequence", start = 1, end = 6 }
+first = { type = "range", min = 1, max = 5 }
+locale = { type = "pick", values = ["en", "fr"] }
+"#,
+        )
+        .unwrap();
+        insta::assert_snapshot!(generated_variables(&generators).unwrap(), @r#"{ "first": 1 + Math.floor(Math.random() * 5), "id": 1 + (exec.scenario.iterationInTest % 6), "locale": pick(["en","fr"]) }"#);
+
+        let generators: BTreeMap<String, Generator> =
+            toml::from_str(r#"n = { type = "range", min = 10, max = 1 }"#).unwrap();
+        insta::assert_snapshot!(generated_variables(&generators).unwrap_err(), @"Invalid generator for variable 'n': Range max 1 is lower than its min 10");
+
+        let generators: BTreeMap<String, Generator> = toml::from_str(
+            r#"n = { type = "sequence", start = -9223372036854775808, end = 9223372036854775807 }"#,
+        )
+        .unwrap();
+        insta::assert_snapshot!(generated_variables(&generators).unwrap_err(), @"Invalid generator for variable 'n': Sequence from -9223372036854775808 to 9223372036854775807 is too large");
+    }
+}
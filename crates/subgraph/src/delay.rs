@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+static DELAYS: LazyLock<RwLock<HashMap<String, Arc<Delay>>>> = LazyLock::new(Default::default);
+
+/// Distribution of the delay, in milliseconds, applied before answering a subgraph request. It's
+/// configured with `{SUBGRAPH}_DELAY_MS` or `DELAY_MS`, either a number of milliseconds or one of:
+///
+/// - `uniform(min, max)`
+/// - `normal(mean, std_dev)`
+/// - `log-normal(median, sigma)`
+/// - `pareto(scale, shape)`, with `scale` as minimum and a longer tail for smaller `shape`
+/// - `bimodal(fast, slow, slow_fraction)`, `fast` and `slow` being delays themselves
+#[derive(Debug, Clone)]
+pub enum Distribution {
+    Constant(f64),
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+    LogNormal {
+        median: f64,
+        sigma: f64,
+    },
+    Pareto {
+        scale: f64,
+        shape: f64,
+    },
+    Bimodal {
+        fast: Box<Distribution>,
+        slow: Box<Distribution>,
+        slow_fraction: f64,
+    },
+}
+
+/// Delay of a subgraph, with its own random generator seeded with `DELAY_SEED` and the subgraph
+/// name for reproducible delays.
+struct Delay {
+    distribution: Option<Distribution>,
+    rng: Mutex<ChaCha8Rng>,
+}
+
+/// Delay to apply before answering a request to the subgraph, if any.
+pub fn sample(subgraph_name: &str) -> Option<Duration> {
+    let delay = {
+        let delays = DELAYS.read().unwrap();
+        delays.get(subgraph_name).cloned()
+    };
+    let delay = delay.unwrap_or_else(|| {
+        DELAYS
+            .write()
+            .unwrap()
+            .entry(subgraph_name.to_string())
+            .or_insert_with(|| Arc::new(Delay::from_env(subgraph_name)))
+            .clone()
+    });
+
+    let distribution = delay.distribution.as_ref()?;
+    let ms = distribution.sample(&mut *delay.rng.lock().unwrap());
+    (ms > 0.0).then(|| Duration::from_secs_f64(ms / 1000.0))
+}
+
+/// Check every delay set in the environment, before any request needs it.
+pub fn validate_env() -> anyhow::Result<()> {
+    for (key, value) in std::env::vars() {
+        if key.ends_with("DELAY_MS") {
+            Distribution::parse(&value)
+                .map_err(|e| anyhow::anyhow!("Invalid {key} '{value}': {e}"))?;
+        }
+    }
+    env_seed("DELAY_SEED")?;
+    Ok(())
+}
+
+/// Seed set with the given variable, 0 by default.
+pub(crate) fn env_seed(name: &str) -> anyhow::Result<u64> {
+    match std::env::var(name) {
+        Ok(seed) => seed
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid {name} '{seed}', expected a number")),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Seed of the random generator of a subgraph, different for every subgraph.
+pub(crate) fn subgraph_seed(seed: u64, subgraph_name: &str) -> u64 {
+    subgraph_name.bytes().fold(seed, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as u64)
+    })
+}
+
+impl Delay {
+    fn from_env(subgraph_name: &str) -> Self {
+        // Check for subgraph-specific delay first, then fall back to general DELAY_MS
+        let distribution = std::env::var(format!("{}_DELAY_MS", subgraph_name.to_uppercase()))
+            .or_else(|_| std::env::var("DELAY_MS"))
+            .ok()
+            .and_then(|value| {
+                Distribution::parse(&value).expect("delays are validated at start-up")
+            });
+
+        let seed = env_seed("DELAY_SEED").expect("delays are validated at start-up");
+        let seed = subgraph_seed(seed, subgraph_name);
+
+        Delay {
+            distribution,
+            rng: Mutex::new(ChaCha8Rng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Distribution {
+    /// Parse a delay, an empty one being no delay at all.
+    pub fn parse(value: &str) -> anyhow::Result<Option<Self>> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut parser = Parser {
+            input: value.trim(),
+        };
+        let distribution = parser.distribution()?;
+        if !parser.input.is_empty() {
+            return Err(anyhow::anyhow!("unexpected '{}'", parser.input));
+        }
+        distribution.validate()?;
+        Ok(Some(distribution))
+    }
+
+    /// Random delay in milliseconds, never negative.
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        let ms = match self {
+            Distribution::Constant(ms) => *ms,
+            Distribution::Uniform { min, max } => min + (max - min) * rng.random::<f64>(),
+            Distribution::Normal { mean, std_dev } => mean + std_dev * standard_normal(rng),
+            Distribution::LogNormal { median, sigma } => {
+                median * (sigma * standard_normal(rng)).exp()
+            }
+            Distribution::Pareto { scale, shape } => {
+                // Inverse of the CDF, with a uniform value in (0, 1]
+                let uniform = 1.0 - rng.random::<f64>();
+                scale / uniform.powf(1.0 / shape)
+            }
+            Distribution::Bimodal {
+                fast,
+                slow,
+                slow_fraction,
+            } => {
+                if rng.random::<f64>() < *slow_fraction {
+                    slow.sample(rng)
+                } else {
+                    fast.sample(rng)
+                }
+            }
+        };
+        ms.max(0.0)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Distribution::Constant(ms) if *ms < 0.0 => {
+                Err(anyhow::anyhow!("delay must not be negative"))
+            }
+            Distribution::Uniform { min, max } if min < &0.0 || max < min => Err(anyhow::anyhow!(
+                "uniform bounds must satisfy 0 <= min <= max"
+            )),
+            Distribution::Normal { std_dev, .. } if *std_dev < 0.0 => {
+                Err(anyhow::anyhow!("normal std_dev must not be negative"))
+            }
+            Distribution::LogNormal { median, sigma } if *median <= 0.0 || *sigma < 0.0 => Err(
+                anyhow::anyhow!("log-normal median must be positive and sigma not negative"),
+            ),
+            Distribution::Pareto { scale, shape } if *scale <= 0.0 || *shape <= 0.0 => {
+                Err(anyhow::anyhow!("pareto scale and shape must be positive"))
+            }
+            Distribution::Bimodal {
+                fast,
+                slow,
+                slow_fraction,
+            } => {
+                if !(0.0..=1.0).contains(slow_fraction) {
+                    return Err(anyhow::anyhow!(
+                        "bimodal slow_fraction must be between 0 and 1"
+                    ));
+                }
+                fast.validate()?;
+                slow.validate()
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Standard normal value with the Box-Muller transform.
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1 = 1.0 - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+}
+
+impl Parser<'_> {
+    fn distribution(&mut self) -> anyhow::Result<Distribution> {
+        if !self.input.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Ok(Distribution::Constant(self.number()?));
+        }
+        let name_len = self
+            .input
+            .find(|c: char| !(c.is_ascii_alphabetic() || c == '-'))
+            .unwrap_or(self.input.len());
+
+        let name = &self.input[..name_len];
+        self.input = self.input[name_len..].trim_start();
+        self.expect('(')?;
+        let distribution = match name {
+            "uniform" => {
+                let (min, max) = self.pair()?;
+                Distribution::Uniform { min, max }
+            }
+            "normal" => {
+                let (mean, std_dev) = self.pair()?;
+                Distribution::Normal { mean, std_dev }
+            }
+            "log-normal" => {
+                let (median, sigma) = self.pair()?;
+                Distribution::LogNormal { median, sigma }
+            }
+            "pareto" => {
+                let (scale, shape) = self.pair()?;
+                Distribution::Pareto { scale, shape }
+            }
+            "bimodal" => {
+                let fast = self.distribution()?;
+                self.expect(',')?;
+                let slow = self.distribution()?;
+                self.expect(',')?;
+                let slow_fraction = self.number()?;
+                Distribution::Bimodal {
+                    fast: Box::new(fast),
+                    slow: Box::new(slow),
+                    slow_fraction,
+                }
+            }
+            _ => return Err(anyhow::anyhow!("unknown distribution '{name}'")),
+        };
+        self.expect(')')?;
+        Ok(distribution)
+    }
+
+    fn pair(&mut self) -> anyhow::Result<(f64, f64)> {
+        let first = self.number()?;
+        self.expect(',')?;
+        let second = self.number()?;
+        Ok((first, second))
+    }
+
+    fn number(&mut self) -> anyhow::Result<f64> {
+        let len = self
+            .input
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+            .unwrap_or(self.input.len());
+        let number = self.input[..len]
+            .parse::<f64>()
+            .map_err(|_| anyhow::anyhow!("expected a number at '{}'", self.input))?;
+        self.input = self.input[len..].trim_start();
+        Ok(number)
+    }
+
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        self.input = self
+            .input
+            .strip_prefix(c)
+            .ok_or_else(|| anyhow::anyhow!("expected '{c}' at '{}'", self.input))?
+            .trim_start();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> String {
+        match Distribution::parse(value) {
+            Ok(distribution) => format!("{distribution:?}"),
+            Err(e) => format!("error: {e}"),
+        }
+    }
+
+    #[test]
+    fn can_parse_every_distribution() {
+        let distributions = [
+            "",
+            "10",
+            "uniform(1, 5)",
+            "normal(10, 2.5)",
+            "log-normal(10, 0.5)",
+            "pareto(5, 1.5)",
+            "bimodal(uniform(1, 2), bimodal(50, pareto(100, 2), 0.1), 0.05)",
+        ]
+        .map(|value| format!("{value:?} => {}", parse(value)));
+        insta::assert_snapshot!(distributions.join("\n"), @r#"
+        "" => None
+        "10" => Some(Constant(10.0))
+        "uniform(1, 5)" => Some(Uniform { min: 1.0, max: 5.0 })
+        "normal(10, 2.5)" => Some(Normal { mean: 10.0, std_dev: 2.5 })
+        "log-normal(10, 0.5)" => Some(LogNormal { median: 10.0, sigma: 0.5 })
+        "pareto(5, 1.5)" => Some(Pareto { scale: 5.0, shape: 1.5 })
+        "bimodal(uniform(1, 2), bimodal(50, pareto(100, 2), 0.1), 0.05)" => Some(Bimodal { fast: Uniform { min: 1.0, max: 2.0 }, slow: Bimodal { fast: Constant(50.0), slow: Pareto { scale: 100.0, shape: 2.0 }, slow_fraction: 0.1 }, slow_fraction: 0.05 })
+        "#);
+    }
+
+    #[test]
+    fn must_reject_invalid_distributions() {
+        let errors = [
+            "uniform(5, 1)",
+            "uniform(1, 5) 10",
+            "gamma(1, 2)",
+            "normal(10)",
+            "bimodal(1, 2, 1.5)",
+            "-5",
+        ]
+        .map(|value| format!("{value:?} => {}", parse(value)));
+        insta::assert_snapshot!(errors.join("\n"), @r#"
+        "uniform(5, 1)" => error: uniform bounds must satisfy 0 <= min <= max
+        "uniform(1, 5) 10" => error: unexpected '10'
+        "gamma(1, 2)" => error: unknown distribution 'gamma'
+        "normal(10)" => error: expected ',' at ')'
+        "bimodal(1, 2, 1.5)" => error: bimodal slow_fraction must be between 0 and 1
+        "-5" => error: delay must not be negative
+        "#);
+    }
+
+    #[test]
+    fn delays_should_be_reproducible_for_a_seed_and_subgraph() {
+        let distribution = Distribution::parse("bimodal(log-normal(10, 0.5), pareto(50, 2), 0.1)")
+            .unwrap()
+            .unwrap();
+        let samples = |seed: u64, subgraph_name: &str| {
+            let mut rng = ChaCha8Rng::seed_from_u64(subgraph_seed(seed, subgraph_name));
+            (0..100)
+                .map(|_| distribution.sample(&mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(samples(42, "accounts"), samples(42, "accounts"));
+        assert_ne!(samples(42, "accounts"), samples(42, "reviews"));
+        assert_ne!(samples(42, "accounts"), samples(7, "accounts"));
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use axum::{
+    body::{Body, Bytes},
+    http,
+    response::{IntoResponse, Response},
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::delay;
+use crate::state::CachedResponse;
+
+static FAULTS: LazyLock<RwLock<HashMap<String, Arc<Faults>>>> = LazyLock::new(Default::default);
+
+/// Number of injected faults of each kind.
+static INJECTED: [AtomicUsize; Fault::ALL.len()] =
+    [const { AtomicUsize::new(0) }; Fault::ALL.len()];
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/// Fault injected instead of a regular response, configured with `{SUBGRAPH}_FAULTS` or `FAULTS`
+/// as comma-separated rates, e.g. `http-error=0.01,partial-null=0.05`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fault {
+    /// HTTP 500 without any GraphQL response.
+    HttpError,
+    /// GraphQL response without data and with an error.
+    GraphqlError,
+    /// Regular response with its first field, or first element of a list, set to null with an
+    /// error at its path.
+    PartialNull,
+    /// Connection dropped after the response headers.
+    ConnectionReset,
+    /// First half of the regular response.
+    TruncatedBody,
+    /// Regular response after `FAULTS_TIMEOUT_MS` (30s by default), beyond gateway timeouts.
+    Timeout,
+}
+
+/// Faults of a subgraph with their rates, with its own random generator seeded with
+/// `FAULTS_SEED` and the subgraph name for reproducible faults.
+struct Faults {
+    rates: Vec<(Fault, f64)>,
+    rng: Mutex<ChaCha8Rng>,
+}
+
+impl Fault {
+    const ALL: [Fault; 6] = [
+        Fault::HttpError,
+        Fault::GraphqlError,
+        Fault::PartialNull,
+        Fault::ConnectionReset,
+        Fault::TruncatedBody,
+        Fault::Timeout,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Fault::HttpError => "http-error",
+            Fault::GraphqlError => "graphql-error",
+            Fault::PartialNull => "partial-null",
+            Fault::ConnectionReset => "connection-reset",
+            Fault::TruncatedBody => "truncated-body",
+            Fault::Timeout => "timeout",
+        }
+    }
+
+    /// Position in `Fault::ALL`, which lists the faults in their declaration order.
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Parse comma-separated fault rates, whose sum can't exceed 1.
+    fn parse_rates(value: &str) -> anyhow::Result<Vec<(Fault, f64)>> {
+        let mut rates = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, rate) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("expected <fault>=<rate>, got '{entry}'"))?;
+            let fault = Fault::ALL
+                .into_iter()
+                .find(|fault| fault.name() == name.trim())
+                .ok_or_else(|| anyhow::anyhow!("unknown fault '{}'", name.trim()))?;
+            let rate: f64 = rate
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid rate '{}' for {name}", rate.trim()))?;
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(anyhow::anyhow!("rate of {name} must be between 0 and 1"));
+            }
+            rates.push((fault, rate));
+        }
+        if rates.iter().map(|(_, rate)| rate).sum::<f64>() > 1.0 {
+            return Err(anyhow::anyhow!("the sum of the fault rates exceeds 1"));
+        }
+        Ok(rates)
+    }
+}
+
+impl Faults {
+    fn from_env(subgraph_name: &str) -> Self {
+        let rates = std::env::var(format!("{}_FAULTS", subgraph_name.to_uppercase()))
+            .or_else(|_| std::env::var("FAULTS"))
+            .ok()
+            .map(|value| Fault::parse_rates(&value).expect("faults are validated at start-up"))
+            .unwrap_or_default();
+
+        let seed = delay::env_seed("FAULTS_SEED").expect("faults are validated at start-up");
+        let seed = delay::subgraph_seed(seed, subgraph_name);
+
+        Faults {
+            rates,
+            rng: Mutex::new(ChaCha8Rng::seed_from_u64(seed)),
+        }
+    }
+}
+
+/// Check every fault configuration set in the environment, before any request needs it.
+pub fn validate_env() -> anyhow::Result<()> {
+    for (key, value) in std::env::vars() {
+        if key.ends_with("FAULTS") {
+            Fault::parse_rates(&value)
+                .map_err(|e| anyhow::anyhow!("Invalid {key} '{value}': {e}"))?;
+        }
+    }
+    if let Ok(value) = std::env::var("FAULTS_TIMEOUT_MS") {
+        value
+            .parse::<u64>()
+            .map_err(|_| anyhow::anyhow!("Invalid FAULTS_TIMEOUT_MS '{value}'"))?;
+    }
+    delay::env_seed("FAULTS_SEED")?;
+    Ok(())
+}
+
+/// Fault to inject in the response to a request of the subgraph, if any.
+pub fn draw(subgraph_name: &str) -> Option<Fault> {
+    let faults = {
+        let faults = FAULTS.read().unwrap();
+        faults.get(subgraph_name).cloned()
+    };
+    let faults = faults.unwrap_or_else(|| {
+        FAULTS
+            .write()
+            .unwrap()
+            .entry(subgraph_name.to_string())
+            .or_insert_with(|| Arc::new(Faults::from_env(subgraph_name)))
+            .clone()
+    });
+    if faults.rates.is_empty() {
+        return None;
+    }
+
+    let mut draw: f64 = faults.rng.lock().unwrap().random();
+    for (fault, rate) in &faults.rates {
+        if draw < *rate {
+            INJECTED[fault.index()].fetch_add(1, Ordering::Relaxed);
+            return Some(*fault);
+        }
+        draw -= rate;
+    }
+    None
+}
+
+/// Response replacing the regular one, which is only computed if needed.
+pub async fn inject<F>(fault: Fault, response: F) -> Response
+where
+    F: Future<Output = CachedResponse>,
+{
+    match fault {
+        Fault::HttpError => (
+            http::StatusCode::INTERNAL_SERVER_ERROR,
+            "Injected subgraph failure",
+        )
+            .into_response(),
+        Fault::GraphqlError => json_response(Bytes::from_static(
+            br#"{"data":null,"errors":[{"message":"Injected subgraph error","extensions":{"code":"INJECTED_FAULT"}}]}"#,
+        )),
+        Fault::PartialNull => {
+            let response = response.await;
+            match partial_null(&response.body) {
+                Some(body) => json_response(body),
+                None => response.into_response(),
+            }
+        }
+        Fault::ConnectionReset => {
+            let stream = futures_util::stream::once(async {
+                Err::<Bytes, _>(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "Injected connection reset",
+                ))
+            });
+            Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from_stream(stream))
+                .unwrap()
+        }
+        Fault::TruncatedBody => json_response(truncate(&response.await.body)),
+        Fault::Timeout => {
+            let timeout_ms = std::env::var("FAULTS_TIMEOUT_MS")
+                .ok()
+                .and_then(|ms| ms.parse().ok())
+                .unwrap_or(DEFAULT_TIMEOUT_MS);
+            tokio::time::sleep(Duration::from_millis(timeout_ms)).await;
+            response.await.into_response()
+        }
+    }
+}
+
+/// Response with its first field, or first element of a list, set to null and an error at its
+/// path. `None` if there's no data to null.
+fn partial_null(body: &[u8]) -> Option<Bytes> {
+    let mut response: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let data = response.get_mut("data")?.as_object_mut()?;
+    let (field, value) = data.iter_mut().next()?;
+
+    let mut path = vec![serde_json::Value::from(field.as_str())];
+    match value {
+        serde_json::Value::Array(items) if !items.is_empty() => {
+            items[0] = serde_json::Value::Null;
+            path.push(0.into());
+        }
+        value => *value = serde_json::Value::Null,
+    }
+
+    let error = serde_json::json!({
+        "message": "Injected subgraph field error",
+        "path": path,
+        "extensions": {"code": "INJECTED_FAULT"},
+    });
+    let object = response.as_object_mut()?;
+    match object
+        .get_mut("errors")
+        .and_then(|errors| errors.as_array_mut())
+    {
+        Some(errors) => errors.push(error),
+        None => {
+            object.insert("errors".to_string(), serde_json::json!([error]));
+        }
+    }
+    serde_json::to_vec(&response).ok().map(Bytes::from)
+}
+
+/// First half of a response body.
+fn truncate(body: &Bytes) -> Bytes {
+    body.slice(..body.len() / 2)
+}
+
+fn json_response(body: Bytes) -> Response {
+    Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(http::header::CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Number of injected faults of each kind, as exposed by `/stats`.
+pub fn snapshot() -> BTreeMap<&'static str, usize> {
+    Fault::ALL
+        .into_iter()
+        .map(|fault| {
+            (
+                fault.name(),
+                INJECTED[fault.index()].load(Ordering::Relaxed),
+            )
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+pub fn reset() {
+    for counter in &INJECTED {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_rates(value: &str) -> String {
+        match Fault::parse_rates(value) {
+            Ok(rates) => format!("{rates:?}"),
+            Err(e) => format!("error: {e}"),
+        }
+    }
+
+    #[test]
+    fn can_parse_fault_rates_whose_sum_does_not_exceed_one() {
+        let rates = [
+            "",
+            "http-error=0.01, partial-null=0.05",
+            "timeout=0.5,truncated-body=0.5",
+            "http-error=0.6,graphql-error=0.5",
+            "http-error",
+            "slow=0.1",
+            "http-error=1.5",
+            "http-error=often",
+        ]
+        .map(|value| format!("{value:?} => {}", parse_rates(value)));
+        insta::assert_snapshot!(rates.join("\n"), @r#"
+        "" => []
+        "http-error=0.01, partial-null=0.05" => [(HttpError, 0.01), (PartialNull, 0.05)]
+        "timeout=0.5,truncated-body=0.5" => [(Timeout, 0.5), (TruncatedBody, 0.5)]
+        "http-error=0.6,graphql-error=0.5" => error: the sum of the fault rates exceeds 1
+        "http-error" => error: expected <fault>=<rate>, got 'http-error'
+        "slow=0.1" => error: unknown fault 'slow'
+        "http-error=1.5" => error: rate of http-error must be between 0 and 1
+        "http-error=often" => error: invalid rate 'often' for http-error
+        "#);
+    }
+
+    fn partial_null(body: &str) -> String {
+        let body = super::partial_null(body.as_bytes()).map(|body| body.to_vec());
+        match body {
+            Some(body) => String::from_utf8(body).unwrap(),
+            None => "None".to_string(),
+        }
+    }
+
+    #[test]
+    fn partial_null_should_null_the_first_field_or_element_with_an_error_at_its_path() {
+        insta::assert_snapshot!(partial_null(r#"{"data":{"me":{"id":"1"},"topProducts":[]}}"#), @r#"{"data":{"me":null,"topProducts":[]},"errors":[{"extensions":{"code":"INJECTED_FAULT"},"message":"Injected subgraph field error","path":["me"]}]}"#);
+        insta::assert_snapshot!(partial_null(r#"{"data":{"topProducts":[{"upc":"1"},{"upc":"2"}]}}"#), @r#"{"data":{"topProducts":[null,{"upc":"2"}]},"errors":[{"extensions":{"code":"INJECTED_FAULT"},"message":"Injected subgraph field error","path":["topProducts",0]}]}"#);
+        insta::assert_snapshot!(partial_null(r#"{"data":{"_entities":[{"reviews":[]},{"reviews":[]}]},"errors":[{"message":"existing"}]}"#), @r#"{"data":{"_entities":[null,{"reviews":[]}]},"errors":[{"message":"existing"},{"extensions":{"code":"INJECTED_FAULT"},"message":"Injected subgraph field error","path":["_entities",0]}]}"#);
+        insta::assert_snapshot!(partial_null(r#"{"data":null,"errors":[{"message":"existing"}]}"#), @"None");
+    }
+
+    #[test]
+    fn truncated_body_should_keep_the_first_half_of_the_response() {
+        let body = Bytes::from_static(br#"{"data":{"me":{"id":"1"}}}"#);
+        insta::assert_snapshot!(String::from_utf8_lossy(&truncate(&body)), @r#"{"data":{"me""#);
+    }
+}
@@ -1,11 +1,19 @@
+mod delay;
+mod faults;
+mod limits;
+mod recording;
 mod state;
+mod stats;
 
 use serde::Serialize;
 pub use state::{AppState, AppStateBuilder};
 use tracing::{debug, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use std::{sync::atomic::Ordering, time::Duration};
+use std::{
+    sync::atomic::Ordering,
+    time::{Instant, SystemTime},
+};
 
 use async_graphql::http::GraphiQLSource;
 use axum::{
@@ -20,7 +28,7 @@ use axum::{
 
 use std::sync::Arc;
 
-use crate::state::CachedResponse;
+use crate::state::{CachedResponse, SubgraphState};
 
 pub async fn serve(state: impl Into<AppState>) -> anyhow::Result<()> {
     tracing_subscriber::registry()
@@ -30,7 +38,14 @@ pub async fn serve(state: impl Into<AppState>) -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer().with_target(std::env::var("RUST_LOG").is_ok()))
         .init();
 
-    let state = Arc::new(state.into());
+    let state = state.into();
+    delay::validate_env()?;
+    faults::validate_env()?;
+    limits::validate_env()?;
+    state::validate_env(&state)?;
+    recording::start().await?;
+
+    let state = Arc::new(state);
     let app = Router::new()
         .route("/graphql/{subgraph_name}", post(graphql_handler))
         .route("/sdl", get(sdl_handler))
@@ -44,7 +59,25 @@ pub async fn serve(state: impl Into<AppState>) -> anyhow::Result<()> {
                 let count = state::COUNT.load(Ordering::Relaxed);
                 let cache_hit = state::CACHE_HIT.load(Ordering::Relaxed);
                 let cache_miss = state::CACHE_MISS.load(Ordering::Relaxed);
-                Json(serde_json::json!({"count": count, "cache_hit": cache_hit, "cache_miss": cache_miss}))
+                Json(serde_json::json!({
+                    "count": count,
+                    "cache_hit": cache_hit,
+                    "cache_miss": cache_miss,
+                    "subgraphs": stats::snapshot(),
+                    "faults": faults::snapshot(),
+                }))
+            }),
+        )
+        .route("/requests", get(recorded_requests_handler))
+        .route(
+            "/stats/reset",
+            post(|| async {
+                state::COUNT.store(0, Ordering::Relaxed);
+                state::CACHE_HIT.store(0, Ordering::Relaxed);
+                state::CACHE_MISS.store(0, Ordering::Relaxed);
+                stats::reset();
+                faults::reset();
+                "OK"
             }),
         )
         .layer(middleware::from_fn(request_logging_middleware));
@@ -53,6 +86,9 @@ pub async fn serve(state: impl Into<AppState>) -> anyhow::Result<()> {
 
     let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
     info!("Listening on http://localhost:{}", port);
+    if let Some(path) = recording::path() {
+        info!("Recording requests to {:?}", path);
+    }
 
     axum::serve(listener, app)
         .with_graceful_shutdown(shutdown_signal())
@@ -84,6 +120,9 @@ async fn graphql_handler(
     //         .into_response();
     // }
 
+    let received = SystemTime::now();
+    let start = Instant::now();
+
     // Get the appropriate subgraph state
     let subgraph = match state.get_subgraph(&subgraph_name) {
         Some(s) => s,
@@ -99,56 +138,121 @@ async fn graphql_handler(
         }
     };
 
-    // Skip health check requests only requesting '{"query":"{ __typename }"}' which is 26 bytes
-    // Adding some extra margin in case gateways don't format it exactly like this. All benchmark
-    // queries are bigger than that.
-    if bytes.len() > 30 {
+    // Requests outside of the measured window, such as the gateway start-up, are excluded by
+    // resetting the stats before the load. Gateway health checks may still happen during it.
+    let counted = !stats::is_health_check(&bytes);
+    if counted {
         state::COUNT.fetch_add(1, Ordering::Relaxed);
+        stats::record(&subgraph_name, &bytes);
     }
 
-    // Check for subgraph-specific delay first, then fall back to general DELAY_MS
-    let delay_ms = std::env::var(format!("{}_DELAY_MS", subgraph_name.to_uppercase()))
-        .or_else(|_| std::env::var("DELAY_MS"))
-        .ok()
-        .and_then(|ms| ms.parse::<u64>().ok());
+    // Capacity-constrained subgraphs queue the requests beyond their concurrency limit, holding
+    // the slot until the response is ready, and reject them once the queue is full.
+    let _slot = if counted {
+        match limits::acquire(&subgraph_name).await {
+            limits::Slot::Rejected => {
+                return response::Response::builder()
+                    .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                    .body(axum::body::Body::from("Subgraph queue is full"))
+                    .unwrap()
+                    .into_response();
+            }
+            slot => Some(slot),
+        }
+    } else {
+        None
+    };
 
-    if let Some(delay_ms) = delay_ms.filter(|&ms| ms > 0) {
-        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    // Only the load draws from the seeded delays, keeping them reproducible.
+    if counted && let Some(delay) = delay::sample(&subgraph_name) {
+        tokio::time::sleep(delay).await;
     }
 
     debug!(subgraph = %subgraph_name, request_body = %String::from_utf8_lossy(bytes.as_ref()), "Received GraphQL request");
 
-    match subgraph.cache.get_value_or_guard_async(&bytes).await {
-        Ok(response) => {
-            state::CACHE_HIT.fetch_add(1, Ordering::Relaxed);
-            response.into_response()
+    let regular_response = async {
+        match subgraph.cache() {
+            None => execute(subgraph, &subgraph_name, &bytes).await,
+            Some(cache) => match cache.get_value_or_guard_async(&bytes).await {
+                Ok(response) => {
+                    if counted {
+                        state::CACHE_HIT.fetch_add(1, Ordering::Relaxed);
+                    }
+                    response
+                }
+                Err(guard) => {
+                    if counted {
+                        state::CACHE_MISS.fetch_add(1, Ordering::Relaxed);
+                    }
+                    let response = execute(subgraph, &subgraph_name, &bytes).await;
+                    let _ = guard.insert(response.clone());
+                    response
+                }
+            },
         }
-        Err(guard) => {
-            state::CACHE_MISS.fetch_add(1, Ordering::Relaxed);
-            let request: async_graphql::Request = serde_json::from_slice(&bytes).unwrap();
-            let response = subgraph.schema.execute(request).await;
-            let body = Bytes::from(serde_json::to_vec(&response).unwrap());
-            debug!(subgraph = %subgraph_name, response_body = %String::from_utf8_lossy(body.as_ref()), "Sending GraphQL response");
-
-            let response = CachedResponse {
-                status: http::StatusCode::OK,
-                headers: {
-                    let mut headers = http::HeaderMap::new();
-                    headers.insert(
-                        http::header::CONTENT_TYPE,
-                        http::HeaderValue::from_static("application/json"),
-                    );
-                    headers.insert(
-                        http::header::CONTENT_LENGTH,
-                        http::HeaderValue::from_str(&body.len().to_string()).unwrap(),
-                    );
-                    headers
-                },
-                body,
-            };
-            let _ = guard.insert(response.clone());
-            response.into_response()
+    };
+
+    // Faults are only injected in the load, not in health checks or tooling requests.
+    let fault = counted.then(|| faults::draw(&subgraph_name)).flatten();
+    let response = match fault {
+        Some(fault) => faults::inject(fault, regular_response).await,
+        None => regular_response.await.into_response(),
+    };
+
+    if counted {
+        recording::record(&subgraph_name, &bytes, received, start.elapsed());
+    }
+
+    response
+}
+
+async fn execute(subgraph: &SubgraphState, subgraph_name: &str, bytes: &Bytes) -> CachedResponse {
+    let request: async_graphql::Request = serde_json::from_slice(bytes).unwrap();
+    let response = subgraph.schema.execute(request).await;
+    let body = Bytes::from(serde_json::to_vec(&response).unwrap());
+    debug!(subgraph = %subgraph_name, response_body = %String::from_utf8_lossy(body.as_ref()), "Sending GraphQL response");
+
+    CachedResponse {
+        status: http::StatusCode::OK,
+        headers: {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("application/json"),
+            );
+            headers.insert(
+                http::header::CONTENT_LENGTH,
+                http::HeaderValue::from_str(&body.len().to_string()).unwrap(),
+            );
+            headers
+        },
+        body,
+    }
+}
+
+/// Requests recorded so far, as JSONL, if `RECORD_REQUESTS` is set.
+async fn recorded_requests_handler() -> impl IntoResponse {
+    let Some(path) = recording::path() else {
+        return response::Response::builder()
+            .status(http::StatusCode::NOT_FOUND)
+            .body(axum::body::Body::from(
+                "Requests are not recorded, set RECORD_REQUESTS",
+            ))
+            .unwrap()
+            .into_response();
+    };
+
+    match tokio::fs::read(path).await {
+        Ok(content) => {
+            ([(http::header::CONTENT_TYPE, "application/jsonl")], content).into_response()
         }
+        Err(e) => response::Response::builder()
+            .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+            .body(axum::body::Body::from(format!(
+                "Could not read the recorded requests: {e}"
+            )))
+            .unwrap()
+            .into_response(),
     }
 }
 
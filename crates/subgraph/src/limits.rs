@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use crate::stats;
+
+static LIMITS: LazyLock<RwLock<HashMap<String, Arc<Option<Limit>>>>> =
+    LazyLock::new(Default::default);
+
+/// Concurrency limit of a subgraph, configured with `{SUBGRAPH}_MAX_CONCURRENCY` or
+/// `MAX_CONCURRENCY`. Requests beyond it wait in a queue bounded by `{SUBGRAPH}_MAX_QUEUE` or
+/// `MAX_QUEUE`, unbounded by default, and are rejected once it's full.
+struct Limit {
+    semaphore: Arc<Semaphore>,
+    max_queue: Option<usize>,
+    waiting: AtomicUsize,
+}
+
+/// Outcome of waiting for a concurrency slot.
+pub enum Slot {
+    /// No limit for this subgraph.
+    Unlimited,
+    /// Slot held until the response is ready.
+    Acquired { _permit: OwnedSemaphorePermit },
+    /// Queue full, the request must be rejected.
+    Rejected,
+}
+
+impl Limit {
+    fn new(max_concurrency: usize, max_queue: Option<usize>) -> Self {
+        Limit {
+            semaphore: Arc::new(Semaphore::new(max_concurrency)),
+            max_queue,
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    fn from_env(subgraph_name: &str) -> Option<Self> {
+        let max_concurrency = env_setting(subgraph_name, "MAX_CONCURRENCY")?;
+        Some(Limit::new(
+            max_concurrency,
+            env_setting(subgraph_name, "MAX_QUEUE"),
+        ))
+    }
+
+    /// Wait for a concurrency slot, unless the queue is full.
+    async fn acquire(&self, subgraph_name: &str) -> Slot {
+        match self.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => return Slot::Acquired { _permit: permit },
+            Err(TryAcquireError::Closed) => unreachable!("the semaphore is never closed"),
+            Err(TryAcquireError::NoPermits) => {}
+        }
+
+        let waiting = self.waiting.fetch_add(1, Ordering::Relaxed);
+        if self.max_queue.is_some_and(|max_queue| waiting >= max_queue) {
+            self.waiting.fetch_sub(1, Ordering::Relaxed);
+            stats::record_rejected(subgraph_name);
+            return Slot::Rejected;
+        }
+
+        stats::record_queued(subgraph_name);
+        let permit = self.semaphore.clone().acquire_owned().await;
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        Slot::Acquired {
+            _permit: permit.expect("the semaphore is never closed"),
+        }
+    }
+}
+
+/// Subgraph-specific setting first, then the general one.
+fn env_setting(subgraph_name: &str, name: &str) -> Option<usize> {
+    std::env::var(format!("{}_{name}", subgraph_name.to_uppercase()))
+        .or_else(|_| std::env::var(name))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .expect("limits are validated at start-up")
+        })
+}
+
+/// Check every limit set in the environment, before any request needs it.
+pub fn validate_env() -> anyhow::Result<()> {
+    validate(std::env::vars().collect())
+}
+
+fn validate(vars: HashMap<String, String>) -> anyhow::Result<()> {
+    let vars: HashMap<String, String> = vars
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .collect();
+    for (key, value) in &vars {
+        if key.ends_with("MAX_CONCURRENCY") || key.ends_with("MAX_QUEUE") {
+            let limit: usize = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid {key} '{value}', expected a number"))?;
+            if key.ends_with("MAX_CONCURRENCY") && limit == 0 {
+                return Err(anyhow::anyhow!("{key} must be positive"));
+            }
+        }
+
+        // Requests only wait in the queue beyond a concurrency limit.
+        if let Some(prefix) = key.strip_suffix("MAX_QUEUE") {
+            let has_limit = if prefix.is_empty() {
+                vars.keys().any(|key| key.ends_with("MAX_CONCURRENCY"))
+            } else {
+                vars.contains_key(&format!("{prefix}MAX_CONCURRENCY"))
+                    || vars.contains_key("MAX_CONCURRENCY")
+            };
+            if !has_limit {
+                return Err(anyhow::anyhow!(
+                    "{key} requires {prefix}MAX_CONCURRENCY, requests only queue beyond a concurrency limit"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Wait for a concurrency slot of the subgraph, if it has a limit.
+pub async fn acquire(subgraph_name: &str) -> Slot {
+    let limit = {
+        let limits = LIMITS.read().unwrap();
+        limits.get(subgraph_name).cloned()
+    };
+    let limit = limit.unwrap_or_else(|| {
+        LIMITS
+            .write()
+            .unwrap()
+            .entry(subgraph_name.to_string())
+            .or_insert_with(|| Arc::new(Limit::from_env(subgraph_name)))
+            .clone()
+    });
+    match limit.as_ref() {
+        Some(limit) => limit.acquire(subgraph_name).await,
+        None => Slot::Unlimited,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(slot: &Slot) -> &'static str {
+        match slot {
+            Slot::Unlimited => "unlimited",
+            Slot::Acquired { .. } => "acquired",
+            Slot::Rejected => "rejected",
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_beyond_the_limit_should_be_queued_then_rejected_once_the_queue_is_full() {
+        let limit = Arc::new(Limit::new(1, Some(1)));
+
+        let first = limit.acquire("limited").await;
+        let second = tokio::spawn({
+            let limit = limit.clone();
+            async move { outcome(&limit.acquire("limited").await) }
+        });
+        // Let the second request wait in the queue.
+        while limit.waiting.load(Ordering::Relaxed) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let third = limit.acquire("limited").await;
+
+        let [first_outcome, third_outcome] = [outcome(&first), outcome(&third)];
+        drop(first);
+        let second = second.await.unwrap();
+
+        let stats = &stats::snapshot()["limited"];
+        let summary = format!(
+            "first: {first_outcome}\nsecond: {second}\nthird: {third_outcome}\nqueued: {}\nrejected: {}",
+            stats.queued, stats.rejected
+        );
+        insta::assert_snapshot!(summary, @r#"
+        first: acquired
+        second: acquired
+        third: rejected
+        queued: 1
+        rejected: 1
+        "#);
+    }
+
+    #[test]
+    fn max_queue_must_come_with_a_concurrency_limit() {
+        let validate = |vars: &[(&str, &str)]| match validate(
+            vars.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        ) {
+            Ok(()) => "ok".to_string(),
+            Err(e) => e.to_string(),
+        };
+        let results = [
+            validate(&[("MAX_CONCURRENCY", "10"), ("MAX_QUEUE", "100")]),
+            validate(&[("MAX_CONCURRENCY", "10"), ("REVIEWS_MAX_QUEUE", "100")]),
+            validate(&[("REVIEWS_MAX_CONCURRENCY", "10"), ("MAX_QUEUE", "100")]),
+            validate(&[("MAX_QUEUE", "100")]),
+            validate(&[
+                ("ACCOUNTS_MAX_CONCURRENCY", "10"),
+                ("REVIEWS_MAX_QUEUE", "100"),
+            ]),
+            validate(&[("MAX_CONCURRENCY", "0")]),
+        ];
+        insta::assert_snapshot!(results.join("\n"), @r#"
+        ok
+        ok
+        ok
+        MAX_QUEUE requires MAX_CONCURRENCY, requests only queue beyond a concurrency limit
+        REVIEWS_MAX_QUEUE requires REVIEWS_MAX_CONCURRENCY, requests only queue beyond a concurrency limit
+        MAX_CONCURRENCY must be positive
+        "#);
+    }
+}
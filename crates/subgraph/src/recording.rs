@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncWriteExt as _, BufWriter};
+use tokio::sync::mpsc;
+
+/// JSONL file in which every subgraph request is recorded, set with `RECORD_REQUESTS`.
+static RECORDER: OnceLock<Recorder> = OnceLock::new();
+
+/// Lines are sent to a single task writing them, so request handlers never wait on the file.
+struct Recorder {
+    path: PathBuf,
+    lines: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+/// Create the recording if `RECORD_REQUESTS` is set, and start writing the recorded requests.
+pub async fn start() -> anyhow::Result<()> {
+    let Some(path) = std::env::var_os("RECORD_REQUESTS").map(PathBuf::from) else {
+        return Ok(());
+    };
+    let file = tokio::fs::File::create(&path)
+        .await
+        .with_context(|| format!("Could not create the request recording {path:?}"))?;
+
+    let (lines, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+    tokio::spawn(async move {
+        let mut writer = BufWriter::new(file);
+        while let Some(line) = receiver.recv().await {
+            let mut result = writer.write_all(&line).await;
+            // Write whatever else is pending before flushing, for `/requests` to serve it.
+            while let Ok(line) = receiver.try_recv() {
+                result = result.and(writer.write_all(&line).await);
+            }
+            if let Err(e) = result.and(writer.flush().await) {
+                tracing::error!("Could not record the requests: {}", e);
+            }
+        }
+    });
+
+    RECORDER
+        .set(Recorder { path, lines })
+        .map_err(|_| anyhow::anyhow!("Requests are already being recorded"))
+}
+
+/// A subgraph request, one JSON object per line of the recording.
+#[derive(Serialize)]
+struct RecordedRequest<'a> {
+    subgraph: &'a str,
+    /// Query with its whitespace collapsed, to compare the queries of different gateways.
+    query: String,
+    operation_name: Option<String>,
+    /// Size of the serialized variables in bytes.
+    variables_size: usize,
+    /// Milliseconds since the UNIX epoch at which the request was received.
+    timestamp: f64,
+    /// Milliseconds spent answering the request, including any delay.
+    latency: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    #[serde(default)]
+    query: String,
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: Option<serde_json::Value>,
+}
+
+/// Path of the recording, if enabled.
+pub fn path() -> Option<&'static PathBuf> {
+    RECORDER.get().map(|recorder| &recorder.path)
+}
+
+/// Record a request received at `received`, if the recording is enabled.
+pub fn record(subgraph_name: &str, body: &[u8], received: SystemTime, latency: Duration) {
+    let Some(recorder) = RECORDER.get() else {
+        return;
+    };
+    let Ok(request) = serde_json::from_slice::<Request>(body) else {
+        return;
+    };
+
+    let record = RecordedRequest {
+        subgraph: subgraph_name,
+        query: request
+            .query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+        operation_name: request.operation_name,
+        variables_size: request
+            .variables
+            .map_or(0, |variables| variables.to_string().len()),
+        timestamp: received
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+            * 1000.0,
+        latency: latency.as_secs_f64() * 1000.0,
+    };
+
+    let mut line = serde_json::to_vec(&record).expect("recorded requests serialize to JSON");
+    line.push(b'\n');
+    // The writer task only stops with the runtime.
+    let _ = recorder.lines.send(line);
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicUsize;
 
 use async_graphql::{Request, Response as GraphQLResponse, SDLExportOptions, Schema};
@@ -58,7 +59,10 @@ where
 }
 
 pub struct SubgraphState {
-    pub cache: Cache,
+    name: Option<String>,
+    /// Response cache, keyed by the request body, created with the first request once the
+    /// settings are validated. `None` when disabled.
+    cache: OnceLock<Option<Cache>>,
     pub schema: Box<dyn BoxedSchema>,
 }
 
@@ -67,26 +71,90 @@ pub enum AppState {
     PerSubgraph(HashMap<String, SubgraphState>),
 }
 
+const DEFAULT_CACHE_SIZE: usize = 1024;
+
 impl SubgraphState {
-    fn new(schema: Box<dyn BoxedSchema>) -> Self {
-        let cache = Cache::with(
-            1024,
-            1024,
-            UnitWeighter,
-            Default::default(),
-            Default::default(),
-        );
-        SubgraphState { cache, schema }
+    fn new(name: Option<&str>, schema: Box<dyn BoxedSchema>) -> Self {
+        SubgraphState {
+            name: name.map(str::to_string),
+            cache: OnceLock::new(),
+            schema,
+        }
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache
+            .get_or_init(|| {
+                let size = cache_size(self.name.as_deref())
+                    .expect("cache settings are validated at start-up")?;
+                Some(Cache::with(
+                    size,
+                    size as u64,
+                    UnitWeighter,
+                    Default::default(),
+                    Default::default(),
+                ))
+            })
+            .as_ref()
+    }
+}
+
+/// Number of cached responses, from `{SUBGRAPH}_CACHE` first, then `SUBGRAPH_CACHE`: `on` (the
+/// default) for 1024 responses, `off` to disable the cache, or the maximum number of responses.
+/// Services with a single schema share one cache between all their subgraphs, so only
+/// `SUBGRAPH_CACHE` applies to them.
+fn cache_size(name: Option<&str>) -> anyhow::Result<Option<usize>> {
+    let value = name
+        .and_then(|name| std::env::var(cache_variable(name)).ok())
+        .or_else(|| std::env::var("SUBGRAPH_CACHE").ok());
+
+    match value {
+        Some(value) => parse_cache_setting(&value),
+        None => Ok(Some(DEFAULT_CACHE_SIZE)),
+    }
+}
+
+fn cache_variable(name: &str) -> String {
+    format!("{}_CACHE", name.to_uppercase())
+}
+
+fn parse_cache_setting(value: &str) -> anyhow::Result<Option<usize>> {
+    match value.trim() {
+        "on" => Ok(Some(DEFAULT_CACHE_SIZE)),
+        "off" | "0" => Ok(None),
+        size => size.parse().map(Some).map_err(|_| {
+            anyhow::anyhow!("expected 'on', 'off' or a number of responses, got '{size}'")
+        }),
     }
 }
 
+/// Check the cache settings of the subgraphs of the service, before any request needs them.
+pub fn validate_env(state: &AppState) -> anyhow::Result<()> {
+    let names: Vec<&str> = match state {
+        AppState::Single(_) => Vec::new(),
+        AppState::PerSubgraph(map) => map.keys().map(String::as_str).collect(),
+    };
+    validate(&names, &std::env::vars().collect())
+}
+
+fn validate(names: &[&str], vars: &HashMap<String, String>) -> anyhow::Result<()> {
+    let keys = std::iter::once("SUBGRAPH_CACHE".to_string())
+        .chain(names.iter().map(|name| cache_variable(name)));
+    for key in keys {
+        if let Some(value) = vars.get(&key) {
+            parse_cache_setting(value).map_err(|e| anyhow::anyhow!("Invalid {key}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
 impl AppState {
     pub fn builder() -> AppStateBuilder {
         AppStateBuilder::new()
     }
 
     pub fn single(schema: Box<dyn BoxedSchema>) -> Self {
-        AppState::Single(SubgraphState::new(schema))
+        AppState::Single(SubgraphState::new(None, schema))
     }
 
     pub fn get_subgraph(&self, name: &str) -> Option<&SubgraphState> {
@@ -115,7 +183,9 @@ impl AppStateBuilder {
     }
 
     pub fn with_subgraph(mut self, name: impl Into<String>, schema: Box<dyn BoxedSchema>) -> Self {
-        self.schemas.insert(name.into(), SubgraphState::new(schema));
+        let name = name.into();
+        let state = SubgraphState::new(Some(&name), schema);
+        self.schemas.insert(name, state);
         self
     }
 
@@ -137,3 +207,48 @@ where
         AppState::single(Box::new(schema))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_setting_must_be_on_off_or_a_number_of_responses() {
+        let settings = ["on", " off ", "0", "10"]
+            .map(|value| format!("{value:?}: {:?}", parse_cache_setting(value).unwrap()));
+        insta::assert_snapshot!(settings.join("\n"), @r#"
+        "on": Some(1024)
+        " off ": None
+        "0": None
+        "10": Some(10)
+        "#);
+
+        let err = parse_cache_setting("lots").unwrap_err();
+        insta::assert_snapshot!(err, @"expected 'on', 'off' or a number of responses, got 'lots'");
+    }
+
+    #[test]
+    fn only_the_cache_settings_of_the_service_subgraphs_should_be_validated() {
+        let vars = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+
+        let unrelated = vars(&[("CARGO_HOME_CACHE", "/tmp"), ("ACCOUNTS_CACHE", "10")]);
+        assert!(validate(&["accounts"], &unrelated).is_ok());
+
+        let errors = [
+            validate(&["accounts"], &vars(&[("ACCOUNTS_CACHE", "lots")])),
+            validate(&[], &vars(&[("SUBGRAPH_CACHE", "yes")])),
+        ]
+        .map(|result| result.unwrap_err().to_string());
+        insta::assert_debug_snapshot!(errors, @r#"
+        [
+            "Invalid ACCOUNTS_CACHE: expected 'on', 'off' or a number of responses, got 'lots'",
+            "Invalid SUBGRAPH_CACHE: expected 'on', 'off' or a number of responses, got 'yes'",
+        ]
+        "#);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use async_graphql::parser::types::Selection;
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+
+static SUBGRAPHS: LazyLock<Subgraphs> = LazyLock::new(Default::default);
+
+/// Counters of every subgraph that received a request, by name.
+#[derive(Default)]
+struct Subgraphs(RwLock<HashMap<String, Arc<RequestCounters>>>);
+
+/// Counters of the requests received by a subgraph.
+#[derive(Default)]
+struct RequestCounters {
+    requests: AtomicUsize,
+    root_requests: AtomicUsize,
+    entities_requests: AtomicUsize,
+    representations: AtomicUsize,
+    bytes: AtomicUsize,
+    queued: AtomicUsize,
+    rejected: AtomicUsize,
+}
+
+/// Snapshot of the counters of a subgraph, as exposed by `/stats`.
+#[derive(Serialize)]
+pub struct RequestStats {
+    pub requests: usize,
+    /// Requests for root fields, as opposed to `_entities` requests.
+    pub root_requests: usize,
+    pub entities_requests: usize,
+    /// Total number of representations sent to `_entities`.
+    pub representations: usize,
+    /// Total size of the request bodies.
+    pub bytes: usize,
+    /// Requests which had to wait for a concurrency slot.
+    pub queued: usize,
+    /// Requests rejected because the queue was full.
+    pub rejected: usize,
+}
+
+/// Only the parts of a request needed to tell its shape.
+#[derive(Deserialize)]
+struct RequestShape {
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    variables: Option<Variables>,
+}
+
+#[derive(Deserialize)]
+struct Variables {
+    #[serde(default)]
+    representations: Option<Vec<IgnoredAny>>,
+}
+
+/// Whether a request only selects `__typename`, as gateways do to check that a subgraph is up.
+pub fn is_health_check(body: &[u8]) -> bool {
+    let Ok(shape) = serde_json::from_slice::<RequestShape>(body) else {
+        return false;
+    };
+    let Ok(document) = async_graphql::parser::parse_query(&shape.query) else {
+        return false;
+    };
+    document.fragments.is_empty()
+        && document.operations.iter().all(|(_, operation)| {
+            operation
+                .node
+                .selection_set
+                .node
+                .items
+                .iter()
+                .all(|selection| match &selection.node {
+                    Selection::Field(field) => field.node.name.node == "__typename",
+                    _ => false,
+                })
+        })
+}
+
+/// Count a request received by a subgraph.
+pub fn record(subgraph_name: &str, body: &[u8]) {
+    SUBGRAPHS.record(subgraph_name, body);
+}
+
+/// Count a request of a subgraph waiting for a concurrency slot.
+pub fn record_queued(subgraph_name: &str) {
+    SUBGRAPHS
+        .counters(subgraph_name)
+        .queued
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a request of a subgraph rejected because its queue was full.
+pub fn record_rejected(subgraph_name: &str) {
+    SUBGRAPHS
+        .counters(subgraph_name)
+        .rejected
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+/// Reset the counters of every subgraph, so that they only cover the measured window.
+pub fn reset() {
+    SUBGRAPHS.reset();
+}
+
+/// Counters of every subgraph that received a request, by name.
+pub fn snapshot() -> BTreeMap<String, RequestStats> {
+    SUBGRAPHS.snapshot()
+}
+
+impl Subgraphs {
+    fn counters(&self, subgraph_name: &str) -> Arc<RequestCounters> {
+        let counters = {
+            let subgraphs = self.0.read().unwrap();
+            subgraphs.get(subgraph_name).cloned()
+        };
+        counters.unwrap_or_else(|| {
+            self.0
+                .write()
+                .unwrap()
+                .entry(subgraph_name.to_string())
+                .or_default()
+                .clone()
+        })
+    }
+
+    fn record(&self, subgraph_name: &str, body: &[u8]) {
+        let counters = self.counters(subgraph_name);
+        counters.requests.fetch_add(1, Ordering::Relaxed);
+        counters.bytes.fetch_add(body.len(), Ordering::Relaxed);
+
+        let shape = serde_json::from_slice::<RequestShape>(body).ok();
+        match shape {
+            Some(shape) if shape.query.contains("_entities") => {
+                counters.entities_requests.fetch_add(1, Ordering::Relaxed);
+                let representations = shape
+                    .variables
+                    .and_then(|variables| variables.representations)
+                    .map_or(0, |representations| representations.len());
+                counters
+                    .representations
+                    .fetch_add(representations, Ordering::Relaxed);
+            }
+            _ => {
+                counters.root_requests.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn reset(&self) {
+        for counters in self.0.read().unwrap().values() {
+            counters.requests.store(0, Ordering::Relaxed);
+            counters.root_requests.store(0, Ordering::Relaxed);
+            counters.entities_requests.store(0, Ordering::Relaxed);
+            counters.representations.store(0, Ordering::Relaxed);
+            counters.bytes.store(0, Ordering::Relaxed);
+            counters.queued.store(0, Ordering::Relaxed);
+            counters.rejected.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> BTreeMap<String, RequestStats> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, counters)| {
+                let stats = RequestStats {
+                    requests: counters.requests.load(Ordering::Relaxed),
+                    root_requests: counters.root_requests.load(Ordering::Relaxed),
+                    entities_requests: counters.entities_requests.load(Ordering::Relaxed),
+                    representations: counters.representations.load(Ordering::Relaxed),
+                    bytes: counters.bytes.load(Ordering::Relaxed),
+                    queued: counters.queued.load(Ordering::Relaxed),
+                    rejected: counters.rejected.load(Ordering::Relaxed),
+                };
+                (name.clone(), stats)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities_requests_should_be_counted_with_their_representations() {
+        let subgraphs = Subgraphs::default();
+        subgraphs.record("accounts", br#"{"query":"query { me { id } }"}"#);
+        subgraphs.record(
+            "reviews",
+            br#"{"query":"query($representations: [_Any!]!) { _entities(representations: $representations) { ... on User { reviews { body } } } }","variables":{"representations":[{"__typename":"User","id":"1"},{"__typename":"User","id":"2"}]}}"#,
+        );
+        subgraphs.record(
+            "reviews",
+            br#"{"query":"query($representations: [_Any!]!) { _entities(representations: $representations) { __typename } }","variables":{"representations":[{"__typename":"User","id":"3"}]}}"#,
+        );
+        // Requests which aren't JSON are still counted, as root requests.
+        subgraphs.record("reviews", b"not json");
+
+        insta::assert_snapshot!(serde_json::to_string_pretty(&subgraphs.snapshot()).unwrap(), @r#"
+        {
+          "accounts": {
+            "requests": 1,
+            "root_requests": 1,
+            "entities_requests": 0,
+            "representations": 0,
+            "bytes": 31,
+            "queued": 0,
+            "rejected": 0
+          },
+          "reviews": {
+            "requests": 3,
+            "root_requests": 1,
+            "entities_requests": 2,
+            "representations": 3,
+            "bytes": 409,
+            "queued": 0,
+            "rejected": 0
+          }
+        }
+        "#);
+    }
+
+    #[test]
+    fn reset_should_zero_the_counters_of_every_subgraph_seen_so_far() {
+        let subgraphs = Subgraphs::default();
+        subgraphs.record("accounts", br#"{"query":"query { me { id } }"}"#);
+        subgraphs.reset();
+        subgraphs.record("products", br#"{"query":"query { topProducts { upc } }"}"#);
+
+        insta::assert_snapshot!(serde_json::to_string_pretty(&subgraphs.snapshot()).unwrap(), @r#"
+        {
+          "accounts": {
+            "requests": 0,
+            "root_requests": 0,
+            "entities_requests": 0,
+            "representations": 0,
+            "bytes": 0,
+            "queued": 0,
+            "rejected": 0
+          },
+          "products": {
+            "requests": 1,
+            "root_requests": 1,
+            "entities_requests": 0,
+            "representations": 0,
+            "bytes": 41,
+            "queued": 0,
+            "rejected": 0
+          }
+        }
+        "#);
+    }
+
+    #[test]
+    fn typename_only_requests_should_be_health_checks() {
+        let requests = [
+            r#"{"query":"{ __typename }"}"#,
+            r#"{"query":"query HealthCheck { __typename }"}"#,
+            r#"{"query":"query { __typename me { id } }"}"#,
+            r#"{"query":"query { me { __typename } }"}"#,
+            r#"{"query":"{ ...Typename } fragment Typename on Query { __typename }"}"#,
+            "not json",
+        ];
+        let health_checks = requests
+            .iter()
+            .map(|request| format!("{}: {request}", is_health_check(request.as_bytes())))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        insta::assert_snapshot!(health_checks, @r#"
+        true: {"query":"{ __typename }"}
+        true: {"query":"query HealthCheck { __typename }"}
+        false: {"query":"query { __typename me { id } }"}
+        false: {"query":"query { me { __typename } }"}
+        false: {"query":"{ ...Typename } fragment Typename on Query { __typename }"}
+        false: not json
+        "#);
+    }
+}